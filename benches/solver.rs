@@ -0,0 +1,155 @@
+// criterion benchmarks over the puzzles of benches/corpus.txt. `cargo bench`
+// runs them all; `cargo bench -- solver/indexed` or `cargo bench -- hard` picks
+// some. the hard and expert solves take up to seconds each, so the solver
+// groups take the fewest samples criterion allows.
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use ricochet_robots::{
+    model::{GameSpec, GameState},
+    serialize,
+    solver::{self, Solver},
+};
+
+const CORPUS: &str = include_str!("corpus.txt");
+const LEVELS: [&str; 4] = ["easy", "medium", "hard", "expert"];
+// enough for every corpus puzzle; the compact solver only allocates what it explores
+const COMPACT_MEMORY_BUDGET: usize = 1 << 30;
+
+struct Puzzle {
+    level: &'static str,
+    optimal_moves: usize,
+    id: &'static str,
+    spec: GameSpec,
+    state: GameState,
+}
+
+fn corpus() -> Vec<Puzzle> {
+    CORPUS
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<&str> = line.split(' ').collect();
+            let [level, optimal_moves, id] = fields[..] else {
+                panic!("corpus lines are `level moves id`, not {:?}", line);
+            };
+            assert!(LEVELS.contains(&level), "unknown level {}", level);
+            let (spec, state) = serialize::load(id);
+            Puzzle {
+                level,
+                optimal_moves: optimal_moves.parse().unwrap(),
+                id,
+                spec,
+                state,
+            }
+        })
+        .collect()
+}
+
+// the corpus states and every state one move away, to give the move
+// generation a mix of robot layouts.
+fn model_states(corpus: &[Puzzle]) -> Vec<(&GameSpec, GameState)> {
+    corpus
+        .iter()
+        .flat_map(|puzzle| {
+            let next_states = puzzle.spec.next_states(&puzzle.state);
+            [puzzle.state.clone()]
+                .into_iter()
+                .chain(next_states)
+                .map(|state| (&puzzle.spec, state))
+        })
+        .collect()
+}
+
+fn bench_model(c: &mut Criterion) {
+    let corpus = corpus();
+    let states = model_states(&corpus);
+
+    c.bench_function("model/next_states", |b| {
+        b.iter(|| {
+            for (spec, state) in &states {
+                black_box(spec.next_states(black_box(state)));
+            }
+        })
+    });
+    c.bench_function("model/prev_states", |b| {
+        b.iter(|| {
+            for (spec, state) in &states {
+                black_box(spec.prev_states(black_box(state)));
+            }
+        })
+    });
+    c.bench_function("model/is_winning_state", |b| {
+        b.iter(|| {
+            for (spec, state) in &states {
+                black_box(spec.is_winning_state(black_box(state)));
+            }
+        })
+    });
+    c.bench_function("serialize/load", |b| {
+        b.iter(|| {
+            for puzzle in &corpus {
+                black_box(serialize::load(black_box(puzzle.id)));
+            }
+        })
+    });
+}
+
+// times `solve` over all the puzzles of each level, checking every length.
+fn bench_solver(c: &mut Criterion, name: &str, mut solve: impl FnMut(&Puzzle) -> usize) {
+    let corpus = corpus();
+    let mut group = c.benchmark_group(format!("solver/{}", name));
+    group.sample_size(10);
+    group.warm_up_time(Duration::from_millis(1));
+    for level in LEVELS {
+        let puzzles: Vec<&Puzzle> = corpus
+            .iter()
+            .filter(|puzzle| puzzle.level == level)
+            .collect();
+        group.bench_function(level, |b| {
+            b.iter(|| {
+                for puzzle in &puzzles {
+                    let moves = solve(puzzle);
+                    assert_eq!(moves, puzzle.optimal_moves, "{}", puzzle.id);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_bfs(c: &mut Criterion) {
+    bench_solver(c, "bfs", |puzzle| {
+        solver::solve_bfs(&puzzle.spec, &puzzle.state)
+            .unwrap()
+            .len()
+    });
+}
+
+fn bench_indexed(c: &mut Criterion) {
+    let mut solver = Solver::new();
+    bench_solver(c, "indexed", |puzzle| {
+        let solution = solver.solve(&puzzle.spec, &puzzle.state, None).unwrap();
+        solution.moves.len()
+    });
+}
+
+fn bench_compact(c: &mut Criterion) {
+    let mut solver = Solver::compact(COMPACT_MEMORY_BUDGET);
+    bench_solver(c, "compact", |puzzle| {
+        let solution = solver.solve(&puzzle.spec, &puzzle.state, None).unwrap();
+        solution.moves.len()
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_model,
+    bench_bfs,
+    bench_indexed,
+    bench_compact
+);
+criterion_main!(benches);
@@ -0,0 +1,196 @@
+#ifndef RICOCHET_H
+#define RICOCHET_H
+
+/* generated by cbindgen from capi/src/lib.rs; do not edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Rows and columns of the wall map passed to `rr_spec_new`.
+ */
+#define RR_WALL_MAP_SIZE 33
+
+/**
+ * Robots in a state: red, blue, green and yellow.
+ */
+#define RR_ROBOT_COUNT 4
+
+typedef enum RrStatus {
+  RR_STATUS_OK = 0,
+  /**
+   * A null pointer, an invalid id or a value outside the board.
+   */
+  RR_STATUS_INVALID_ARGUMENT = 1,
+  /**
+   * No robot can ever reach the goal.
+   */
+  RR_STATUS_UNSOLVABLE = 2,
+  /**
+   * The search ran out of states without reaching the goal.
+   */
+  RR_STATUS_NO_SOLUTION = 3,
+  RR_STATUS_TIMED_OUT = 4,
+  /**
+   * The search needed more memory than its budget.
+   */
+  RR_STATUS_STATE_LIMIT = 5,
+  /**
+   * A bug in the solver. The handles stay valid.
+   */
+  RR_STATUS_PANIC = 6,
+} RrStatus;
+
+/**
+ * A board: its walls and goals.
+ */
+typedef struct RrGameSpec RrGameSpec;
+
+/**
+ * The positions of the robots.
+ */
+typedef struct RrGameState RrGameState;
+
+typedef struct RrPoint {
+  int8_t row;
+  int8_t column;
+} RrPoint;
+
+typedef struct RrGoal {
+  struct RrPoint position;
+  /**
+   * The robot that has to reach the goal, or -1 for any robot.
+   */
+  int8_t robot;
+} RrGoal;
+
+typedef struct RrMove {
+  uint8_t robot;
+  /**
+   * 0 up, 1 down, 2 left, 3 right.
+   */
+  uint8_t direction;
+} RrMove;
+
+/**
+ * Filled by `rr_solve` and released with `rr_solution_free`.
+ */
+typedef struct RrSolution {
+  /**
+   * `move_count` moves, or null when the solve failed.
+   */
+  struct RrMove *moves;
+  size_t move_count;
+  /**
+   * Also set when the search gives up.
+   */
+  size_t explored_states;
+} RrSolution;
+
+/**
+ * Loads a kaseken id, as in the id= parameter of a replay url.
+ *
+ * # Safety
+ * `id` is a nul terminated string. On `RR_STATUS_OK` `*spec` and `*state`
+ * hold new handles for the caller to free.
+ */
+enum RrStatus rr_load_id(const char *id, struct RrGameSpec **spec, struct RrGameState **state);
+
+/**
+ * Builds a board from its wall map and goals.
+ *
+ * # Safety
+ * `walls` points to `RR_WALL_MAP_SIZE * RR_WALL_MAP_SIZE` bytes, row by row,
+ * nonzero for a wall: cell (r, c) sits at (2r+1, 2c+1) and the walls around it
+ * at the neighbouring entries. Every border cell needs its outer wall. `goals`
+ * points to `goal_count` goals, at least one. On `RR_STATUS_OK` `*spec` holds a
+ * new handle for the caller to free.
+ */
+enum RrStatus rr_spec_new(const uint8_t *walls,
+                          const struct RrGoal *goals,
+                          size_t goal_count,
+                          struct RrGameSpec **spec);
+
+/**
+ * Places the robots.
+ *
+ * # Safety
+ * `robots` points to `RR_ROBOT_COUNT` distinct cells. On `RR_STATUS_OK`
+ * `*state` holds a new handle for the caller to free.
+ */
+enum RrStatus rr_state_new(const struct RrPoint *robots, struct RrGameState **state);
+
+/**
+ * Reads the robot positions back.
+ *
+ * # Safety
+ * `state` is a live handle and `robots` has room for `RR_ROBOT_COUNT` cells.
+ */
+enum RrStatus rr_state_robots(const struct RrGameState *state, struct RrPoint *robots);
+
+/**
+ * Finds a shortest solution. With a `memory_budget` of 0 the solver allocates
+ * its 512MB visited set and has no state limit; otherwise it stays within
+ * about that many bytes and gives up with `RR_STATUS_STATE_LIMIT`. A
+ * `timeout_ms` of 0 waits for as long as the search takes.
+ *
+ * # Safety
+ * `spec` and `state` are live handles. `*solution` is overwritten, so it must
+ * not hold moves that weren't freed; release it with `rr_solution_free` even
+ * when the solve fails.
+ */
+enum RrStatus rr_solve(const struct RrGameSpec *spec,
+                       const struct RrGameState *state,
+                       size_t memory_budget,
+                       uint64_t timeout_ms,
+                       struct RrSolution *solution);
+
+/**
+ * Frees the moves of a solution and resets it. Freeing twice is harmless.
+ *
+ * # Safety
+ * `solution` is null or was filled by `rr_solve`.
+ */
+void rr_solution_free(struct RrSolution *solution);
+
+/**
+ * Applies one move, writing the new state to a new handle.
+ *
+ * # Safety
+ * `spec` and `state` are live handles. On `RR_STATUS_OK` `*next` holds a new
+ * handle for the caller to free.
+ */
+enum RrStatus rr_apply_move(const struct RrGameSpec *spec,
+                            const struct RrGameState *state,
+                            struct RrMove game_move,
+                            struct RrGameState **next);
+
+/**
+ * Whether a robot stands on the goal it needs.
+ *
+ * # Safety
+ * `spec` and `state` are live handles.
+ */
+bool rr_is_winning_state(const struct RrGameSpec *spec, const struct RrGameState *state);
+
+/**
+ * # Safety
+ * `spec` is null or a live handle, which is invalid afterwards.
+ */
+void rr_spec_free(struct RrGameSpec *spec);
+
+/**
+ * # Safety
+ * `state` is null or a live handle, which is invalid afterwards.
+ */
+void rr_state_free(struct RrGameState *state);
+
+/**
+ * A static description of a status, for error messages.
+ */
+const char *rr_status_message(enum RrStatus status);
+
+#endif  /* RICOCHET_H */
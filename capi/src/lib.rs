@@ -0,0 +1,435 @@
+// a c abi over the solver, declared by include/ricochet.h. specs and states are
+// opaque handles that the rr_*_new functions make and rr_*_free release; every
+// other call reports an `RrStatus` and writes its results through pointers. the
+// `///` comments are copied into the header, so they are written for c callers.
+// tests/c.rs fails while the header is stale and leaves the regenerated one in
+// target/tmp, or run `cbindgen --config cbindgen.toml --output include/ricochet.h`.
+
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+use std::{ptr, slice};
+
+use ricochet_robots::{
+    model::{
+        has_border_walls, Direction, GameMove, GameSpec, GameState, Goal, Point, TargetType,
+        BOARD_SIZE, ROBOT_COUNT, WALL_MAP_SIZE,
+    },
+    serialize,
+    solver::{SolveError, Solver},
+};
+
+/// Rows and columns of the wall map passed to `rr_spec_new`.
+pub const RR_WALL_MAP_SIZE: usize = 33;
+/// Robots in a state: red, blue, green and yellow.
+pub const RR_ROBOT_COUNT: usize = 4;
+
+const _: () = assert!(RR_WALL_MAP_SIZE == WALL_MAP_SIZE && RR_ROBOT_COUNT == ROBOT_COUNT);
+
+/// A board: its walls and goals.
+pub struct RrGameSpec(GameSpec);
+
+/// The positions of the robots.
+pub struct RrGameState(GameState);
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RrStatus {
+    Ok = 0,
+    /// A null pointer, an invalid id or a value outside the board.
+    InvalidArgument = 1,
+    /// No robot can ever reach the goal.
+    Unsolvable = 2,
+    /// The search ran out of states without reaching the goal.
+    NoSolution = 3,
+    TimedOut = 4,
+    /// The search needed more memory than its budget.
+    StateLimit = 5,
+    /// A bug in the solver. The handles stay valid.
+    Panic = 6,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RrPoint {
+    pub row: i8,
+    pub column: i8,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RrGoal {
+    pub position: RrPoint,
+    /// The robot that has to reach the goal, or -1 for any robot.
+    pub robot: i8,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RrMove {
+    pub robot: u8,
+    /// 0 up, 1 down, 2 left, 3 right.
+    pub direction: u8,
+}
+
+/// Filled by `rr_solve` and released with `rr_solution_free`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct RrSolution {
+    /// `move_count` moves, or null when the solve failed.
+    pub moves: *mut RrMove,
+    pub move_count: usize,
+    /// Also set when the search gives up.
+    pub explored_states: usize,
+}
+
+fn catch_panic(f: impl FnOnce() -> RrStatus) -> RrStatus {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(RrStatus::Panic)
+}
+
+fn on_board(point: RrPoint) -> bool {
+    (0..BOARD_SIZE as i8).contains(&point.row) && (0..BOARD_SIZE as i8).contains(&point.column)
+}
+
+fn into_handle<T>(value: T, out: *mut *mut T) {
+    unsafe { *out = Box::into_raw(Box::new(value)) };
+}
+
+/// Loads a kaseken id, as in the id= parameter of a replay url.
+///
+/// # Safety
+/// `id` is a nul terminated string. On `RR_STATUS_OK` `*spec` and `*state`
+/// hold new handles for the caller to free.
+#[no_mangle]
+pub unsafe extern "C" fn rr_load_id(
+    id: *const c_char,
+    spec: *mut *mut RrGameSpec,
+    state: *mut *mut RrGameState,
+) -> RrStatus {
+    catch_panic(|| {
+        if id.is_null() || spec.is_null() || state.is_null() {
+            return RrStatus::InvalidArgument;
+        }
+        let Ok(id) = unsafe { CStr::from_ptr(id) }.to_str() else {
+            return RrStatus::InvalidArgument;
+        };
+        if !serialize::is_valid_id(id) {
+            return RrStatus::InvalidArgument;
+        }
+        let (loaded_spec, loaded_state) = serialize::load(id);
+        into_handle(RrGameSpec(loaded_spec), spec);
+        into_handle(RrGameState(loaded_state), state);
+        RrStatus::Ok
+    })
+}
+
+/// Builds a board from its wall map and goals.
+///
+/// # Safety
+/// `walls` points to `RR_WALL_MAP_SIZE * RR_WALL_MAP_SIZE` bytes, row by row,
+/// nonzero for a wall: cell (r, c) sits at (2r+1, 2c+1) and the walls around it
+/// at the neighbouring entries. Every border cell needs its outer wall. `goals`
+/// points to `goal_count` goals, at least one. On `RR_STATUS_OK` `*spec` holds a
+/// new handle for the caller to free.
+#[no_mangle]
+pub unsafe extern "C" fn rr_spec_new(
+    walls: *const u8,
+    goals: *const RrGoal,
+    goal_count: usize,
+    spec: *mut *mut RrGameSpec,
+) -> RrStatus {
+    catch_panic(|| {
+        if walls.is_null() || goals.is_null() || goal_count == 0 || spec.is_null() {
+            return RrStatus::InvalidArgument;
+        }
+        let walls = unsafe { slice::from_raw_parts(walls, WALL_MAP_SIZE * WALL_MAP_SIZE) };
+        let mut wall_map = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+        for (row, values) in wall_map.iter_mut().zip(walls.chunks(WALL_MAP_SIZE)) {
+            for (wall, &value) in row.iter_mut().zip(values) {
+                *wall = value != 0;
+            }
+        }
+        if !has_border_walls(&wall_map) {
+            return RrStatus::InvalidArgument;
+        }
+        let mut spec_goals = Vec::with_capacity(goal_count);
+        for goal in unsafe { slice::from_raw_parts(goals, goal_count) } {
+            let target_type = match goal.robot {
+                -1 => TargetType::Any,
+                robot if (0..ROBOT_COUNT as i8).contains(&robot) => {
+                    TargetType::Particular(robot as usize)
+                }
+                _ => return RrStatus::InvalidArgument,
+            };
+            if !on_board(goal.position) {
+                return RrStatus::InvalidArgument;
+            }
+            spec_goals.push(Goal {
+                position: Point::new(goal.position.row, goal.position.column),
+                target_type,
+            });
+        }
+        into_handle(RrGameSpec(GameSpec::new(wall_map, spec_goals)), spec);
+        RrStatus::Ok
+    })
+}
+
+/// Places the robots.
+///
+/// # Safety
+/// `robots` points to `RR_ROBOT_COUNT` distinct cells. On `RR_STATUS_OK`
+/// `*state` holds a new handle for the caller to free.
+#[no_mangle]
+pub unsafe extern "C" fn rr_state_new(
+    robots: *const RrPoint,
+    state: *mut *mut RrGameState,
+) -> RrStatus {
+    catch_panic(|| {
+        if robots.is_null() || state.is_null() {
+            return RrStatus::InvalidArgument;
+        }
+        let robots = unsafe { slice::from_raw_parts(robots, ROBOT_COUNT) };
+        for (i, &robot) in robots.iter().enumerate() {
+            if !on_board(robot) || robots[..i].contains(&robot) {
+                return RrStatus::InvalidArgument;
+            }
+        }
+        let robots = std::array::from_fn(|i| Point::new(robots[i].row, robots[i].column));
+        into_handle(RrGameState(GameState { robots }), state);
+        RrStatus::Ok
+    })
+}
+
+/// Reads the robot positions back.
+///
+/// # Safety
+/// `state` is a live handle and `robots` has room for `RR_ROBOT_COUNT` cells.
+#[no_mangle]
+pub unsafe extern "C" fn rr_state_robots(
+    state: *const RrGameState,
+    robots: *mut RrPoint,
+) -> RrStatus {
+    if state.is_null() || robots.is_null() {
+        return RrStatus::InvalidArgument;
+    }
+    let state = unsafe { &(*state).0 };
+    let robots = unsafe { slice::from_raw_parts_mut(robots, ROBOT_COUNT) };
+    for (out, robot) in robots.iter_mut().zip(&state.robots) {
+        *out = RrPoint {
+            row: robot.r,
+            column: robot.c,
+        };
+    }
+    RrStatus::Ok
+}
+
+/// Finds a shortest solution. With a `memory_budget` of 0 the solver allocates
+/// its 512MB visited set and has no state limit; otherwise it stays within
+/// about that many bytes and gives up with `RR_STATUS_STATE_LIMIT`. A
+/// `timeout_ms` of 0 waits for as long as the search takes.
+///
+/// # Safety
+/// `spec` and `state` are live handles. `*solution` is overwritten, so it must
+/// not hold moves that weren't freed; release it with `rr_solution_free` even
+/// when the solve fails.
+#[no_mangle]
+pub unsafe extern "C" fn rr_solve(
+    spec: *const RrGameSpec,
+    state: *const RrGameState,
+    memory_budget: usize,
+    timeout_ms: u64,
+    solution: *mut RrSolution,
+) -> RrStatus {
+    if solution.is_null() {
+        return RrStatus::InvalidArgument;
+    }
+    let solution = unsafe { &mut *solution };
+    *solution = RrSolution {
+        moves: ptr::null_mut(),
+        move_count: 0,
+        explored_states: 0,
+    };
+    if spec.is_null() || state.is_null() {
+        return RrStatus::InvalidArgument;
+    }
+    let (spec, state) = unsafe { (&(*spec).0, &(*state).0) };
+    catch_panic(|| {
+        let mut solver = match memory_budget {
+            0 => Solver::new(),
+            memory_budget => Solver::compact(memory_budget),
+        };
+        let deadline = (timeout_ms > 0).then(|| Instant::now() + Duration::from_millis(timeout_ms));
+        let (stats, status) = match solver.solve(spec, state, deadline) {
+            Ok(found) => {
+                let moves: Box<[RrMove]> = found
+                    .moves
+                    .iter()
+                    .map(|game_move| RrMove {
+                        robot: game_move.robot_index,
+                        direction: game_move.direction as u8,
+                    })
+                    .collect();
+                solution.move_count = moves.len();
+                solution.moves = Box::into_raw(moves) as *mut RrMove;
+                (found.stats, RrStatus::Ok)
+            }
+            Err(SolveError::Unsolvable(_)) => return RrStatus::Unsolvable,
+            Err(SolveError::NoSolution(stats)) => (stats, RrStatus::NoSolution),
+            Err(SolveError::TimedOut(stats)) => (stats, RrStatus::TimedOut),
+            Err(SolveError::StateLimit(stats)) => (stats, RrStatus::StateLimit),
+        };
+        solution.explored_states = stats.explored_states;
+        status
+    })
+}
+
+/// Frees the moves of a solution and resets it. Freeing twice is harmless.
+///
+/// # Safety
+/// `solution` is null or was filled by `rr_solve`.
+#[no_mangle]
+pub unsafe extern "C" fn rr_solution_free(solution: *mut RrSolution) {
+    let Some(solution) = (unsafe { solution.as_mut() }) else {
+        return;
+    };
+    if !solution.moves.is_null() {
+        let moves = ptr::slice_from_raw_parts_mut(solution.moves, solution.move_count);
+        drop(unsafe { Box::from_raw(moves) });
+    }
+    solution.moves = ptr::null_mut();
+    solution.move_count = 0;
+}
+
+/// Applies one move, writing the new state to a new handle.
+///
+/// # Safety
+/// `spec` and `state` are live handles. On `RR_STATUS_OK` `*next` holds a new
+/// handle for the caller to free.
+#[no_mangle]
+pub unsafe extern "C" fn rr_apply_move(
+    spec: *const RrGameSpec,
+    state: *const RrGameState,
+    game_move: RrMove,
+    next: *mut *mut RrGameState,
+) -> RrStatus {
+    if spec.is_null() || state.is_null() || next.is_null() {
+        return RrStatus::InvalidArgument;
+    }
+    let direction = match game_move.direction {
+        0 => Direction::Up,
+        1 => Direction::Down,
+        2 => Direction::Left,
+        3 => Direction::Right,
+        _ => return RrStatus::InvalidArgument,
+    };
+    if game_move.robot as usize >= ROBOT_COUNT {
+        return RrStatus::InvalidArgument;
+    }
+    let game_move = GameMove {
+        robot_index: game_move.robot,
+        direction,
+    };
+    let (spec, state) = unsafe { (&(*spec).0, &(*state).0) };
+    into_handle(RrGameState(spec.apply_move(state, &game_move)), next);
+    RrStatus::Ok
+}
+
+/// Whether a robot stands on the goal it needs.
+///
+/// # Safety
+/// `spec` and `state` are live handles.
+#[no_mangle]
+pub unsafe extern "C" fn rr_is_winning_state(
+    spec: *const RrGameSpec,
+    state: *const RrGameState,
+) -> bool {
+    if spec.is_null() || state.is_null() {
+        return false;
+    }
+    unsafe { (*spec).0.is_winning_state(&(*state).0) }
+}
+
+/// # Safety
+/// `spec` is null or a live handle, which is invalid afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rr_spec_free(spec: *mut RrGameSpec) {
+    if !spec.is_null() {
+        drop(unsafe { Box::from_raw(spec) });
+    }
+}
+
+/// # Safety
+/// `state` is null or a live handle, which is invalid afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn rr_state_free(state: *mut RrGameState) {
+    if !state.is_null() {
+        drop(unsafe { Box::from_raw(state) });
+    }
+}
+
+/// A static description of a status, for error messages.
+#[no_mangle]
+pub extern "C" fn rr_status_message(status: RrStatus) -> *const c_char {
+    let message = match status {
+        RrStatus::Ok => c"ok",
+        RrStatus::InvalidArgument => c"invalid argument",
+        RrStatus::Unsolvable => c"no robot can reach the goal",
+        RrStatus::NoSolution => c"no solution",
+        RrStatus::TimedOut => c"timed out",
+        RrStatus::StateLimit => c"gave up at the memory budget",
+        RrStatus::Panic => c"internal error",
+    };
+    message.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 7 move puzzle from input.txt
+    const ID: &CStr = c"rKHKNKXIrKM--7--_n---m-----__--Vv------Yv-N-B-_-----Ln-L-m--L-RJk---_XRL--R_-X-g1--j-n--NZ07-XXZf----K--N-BL---Vv----7----X----Zk-----Lj--R__---RLX--n-m------_leXKXAXKVeWDn94ytfICCphpWL97KIP4sVqKkqtUi";
+
+    #[test]
+    fn test_solve_handles() {
+        unsafe {
+            let (mut spec, mut state) = (ptr::null_mut(), ptr::null_mut());
+            assert_eq!(
+                rr_load_id(c"garbage".as_ptr(), &mut spec, &mut state),
+                RrStatus::InvalidArgument
+            );
+            assert_eq!(rr_load_id(ID.as_ptr(), &mut spec, &mut state), RrStatus::Ok);
+
+            let mut solution = RrSolution {
+                moves: ptr::null_mut(),
+                move_count: 0,
+                explored_states: 0,
+            };
+            assert_eq!(
+                rr_solve(spec, state, 1 << 10, 0, &mut solution),
+                RrStatus::StateLimit
+            );
+            assert!(solution.moves.is_null() && solution.explored_states > 0);
+            assert_eq!(
+                rr_solve(spec, state, 64 << 20, 0, &mut solution),
+                RrStatus::Ok
+            );
+            assert_eq!(solution.move_count, 7);
+
+            let mut current = state;
+            for game_move in slice::from_raw_parts(solution.moves, solution.move_count) {
+                let mut next = ptr::null_mut();
+                assert_eq!(
+                    rr_apply_move(spec, current, *game_move, &mut next),
+                    RrStatus::Ok
+                );
+                rr_state_free(current);
+                current = next;
+            }
+            assert!(rr_is_winning_state(spec, current));
+            rr_solution_free(&mut solution);
+            rr_solution_free(&mut solution);
+            rr_state_free(current);
+            rr_spec_free(spec);
+        }
+    }
+}
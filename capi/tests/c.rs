@@ -0,0 +1,61 @@
+// checks that include/ricochet.h matches the library, then compiles
+// tests/test_ricochet.c against the cdylib cargo built next to this test
+// binary and runs it. needs a c compiler, `cc` or $CC.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fs};
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+#[test]
+fn test_header_is_current() {
+    let config =
+        cbindgen::Config::from_file(Path::new(MANIFEST_DIR).join("cbindgen.toml")).unwrap();
+    let mut generated = Vec::new();
+    cbindgen::generate_with_config(MANIFEST_DIR, config)
+        .unwrap()
+        .write(&mut generated);
+    let header = Path::new(MANIFEST_DIR).join("include/ricochet.h");
+    if fs::read(&header).ok().as_ref() != Some(&generated) {
+        let fresh = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("ricochet.h");
+        fs::write(&fresh, &generated).unwrap();
+        panic!(
+            "{} is stale; the generated header is at {}",
+            header.display(),
+            fresh.display()
+        );
+    }
+}
+
+#[test]
+fn test_c_program() {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let library = deps.join(format!(
+        "{}ricochet{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    ));
+    assert!(library.exists(), "{} was not built", library.display());
+
+    let program = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("test_ricochet");
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-o"])
+        .arg(&program)
+        .arg(Path::new(MANIFEST_DIR).join("tests/test_ricochet.c"))
+        .arg("-I")
+        .arg(Path::new(MANIFEST_DIR).join("include"))
+        .arg("-L")
+        .arg(&deps)
+        .arg("-lricochet")
+        .status()
+        .expect("failed to run the c compiler");
+    assert!(status.success());
+
+    let status = Command::new(&program)
+        .env("LD_LIBRARY_PATH", &deps)
+        .env("DYLD_LIBRARY_PATH", &deps)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
@@ -0,0 +1,120 @@
+/* exercises include/ricochet.h the way an embedding application would. run by
+ * tests/c.rs; exits nonzero on the first failed check. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "ricochet.h"
+
+#define CHECK(condition)                                                        \
+    do {                                                                        \
+        if (!(condition)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__,    \
+                    #condition);                                                \
+            return 1;                                                           \
+        }                                                                       \
+    } while (0)
+
+#define CHECK_STATUS(call, expected)                                            \
+    do {                                                                        \
+        RrStatus status = (call);                                               \
+        if (status != (expected)) {                                             \
+            fprintf(stderr, "%s:%d: %s: %s\n", __FILE__, __LINE__, #call,       \
+                    rr_status_message(status));                                 \
+            return 1;                                                           \
+        }                                                                       \
+    } while (0)
+
+/* a 7 move puzzle from input.txt */
+static const char *ID =
+    "rKHKNKXIrKM--7--_n---m-----__--Vv------Yv-N-B-_-----Ln-L-m--L-RJk---_XRL--R_-X-g1--j-n--NZ07-"
+    "XXZf----K--N-BL---Vv----7----X----Zk-----Lj--R__---RLX--n-m------_leXKXAXKVeWDn94ytfICCphpWL97KIP4sVqKkqtUi";
+
+/* applies the moves of a solution and checks that they win. */
+static int check_solution(const RrGameSpec *spec, const RrGameState *state,
+                          const RrSolution *solution) {
+    RrGameState *current = NULL;
+    RrPoint robots[RR_ROBOT_COUNT];
+    CHECK_STATUS(rr_state_robots(state, robots), RR_STATUS_OK);
+    CHECK_STATUS(rr_state_new(robots, &current), RR_STATUS_OK);
+    for (size_t i = 0; i < solution->move_count; i++) {
+        RrGameState *next = NULL;
+        CHECK_STATUS(rr_apply_move(spec, current, solution->moves[i], &next), RR_STATUS_OK);
+        rr_state_free(current);
+        current = next;
+    }
+    int won = rr_is_winning_state(spec, current);
+    rr_state_free(current);
+    CHECK(won);
+    return 0;
+}
+
+static int test_load_id(void) {
+    RrGameSpec *spec = NULL;
+    RrGameState *state = NULL;
+    CHECK_STATUS(rr_load_id("garbage", &spec, &state), RR_STATUS_INVALID_ARGUMENT);
+    CHECK(spec == NULL && state == NULL);
+    CHECK_STATUS(rr_load_id(ID, &spec, &state), RR_STATUS_OK);
+
+    RrSolution solution;
+    CHECK_STATUS(rr_solve(spec, state, 1 << 10, 0, &solution), RR_STATUS_STATE_LIMIT);
+    CHECK(solution.moves == NULL && solution.explored_states > 0);
+    rr_solution_free(&solution);
+
+    CHECK_STATUS(rr_solve(spec, state, 64 << 20, 0, &solution), RR_STATUS_OK);
+    CHECK(solution.move_count == 7);
+    if (check_solution(spec, state, &solution) != 0) {
+        return 1;
+    }
+    rr_solution_free(&solution);
+    CHECK(solution.moves == NULL);
+
+    rr_state_free(state);
+    rr_spec_free(spec);
+    return 0;
+}
+
+static int test_raw_walls(void) {
+    /* an empty board: only the border walls */
+    uint8_t walls[RR_WALL_MAP_SIZE * RR_WALL_MAP_SIZE];
+    memset(walls, 0, sizeof walls);
+    for (int i = 0; i < RR_WALL_MAP_SIZE; i++) {
+        walls[i] = 1;
+        walls[(RR_WALL_MAP_SIZE - 1) * RR_WALL_MAP_SIZE + i] = 1;
+        walls[i * RR_WALL_MAP_SIZE] = 1;
+        walls[i * RR_WALL_MAP_SIZE + RR_WALL_MAP_SIZE - 1] = 1;
+    }
+    RrGoal goals[] = {{{0, 15}, 0}};
+    RrGoal off_board[] = {{{16, 0}, -1}};
+    RrGameSpec *spec = NULL;
+    CHECK_STATUS(rr_spec_new(walls, off_board, 1, &spec), RR_STATUS_INVALID_ARGUMENT);
+    CHECK_STATUS(rr_spec_new(walls, goals, 0, &spec), RR_STATUS_INVALID_ARGUMENT);
+    walls[1] = 0; /* the top wall of (0, 0) */
+    CHECK_STATUS(rr_spec_new(walls, goals, 1, &spec), RR_STATUS_INVALID_ARGUMENT);
+    walls[1] = 1;
+    CHECK_STATUS(rr_spec_new(walls, goals, 1, &spec), RR_STATUS_OK);
+
+    RrPoint shared[RR_ROBOT_COUNT] = {{0, 0}, {0, 0}, {15, 15}, {15, 0}};
+    RrPoint robots[RR_ROBOT_COUNT] = {{0, 0}, {5, 5}, {15, 15}, {15, 0}};
+    RrGameState *state = NULL;
+    CHECK_STATUS(rr_state_new(shared, &state), RR_STATUS_INVALID_ARGUMENT);
+    CHECK_STATUS(rr_state_new(robots, &state), RR_STATUS_OK);
+
+    RrSolution solution;
+    CHECK_STATUS(rr_solve(spec, state, 1 << 20, 0, &solution), RR_STATUS_OK);
+    CHECK(solution.move_count == 1);
+    CHECK(solution.moves[0].robot == 0 && solution.moves[0].direction == 3);
+    rr_solution_free(&solution);
+
+    rr_state_free(state);
+    rr_spec_free(spec);
+    return 0;
+}
+
+int main(void) {
+    if (test_load_id() != 0 || test_raw_walls() != 0) {
+        return 1;
+    }
+    puts("ok");
+    return 0;
+}
@@ -0,0 +1,471 @@
+// python bindings: kaseken ids, specs and states built from python lists, the
+// solvers and the puzzle generator. robots are indices (red, blue, green,
+// yellow), positions are (row, column) and directions are "up", "down",
+// "left" and "right". the *_array methods return numpy arrays and only need
+// numpy when they are called.
+
+use std::time::{Duration, Instant};
+
+use numpy::{PyArray1, PyArray2, PyArrayMethods};
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyMemoryError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use ricochet_robots::{
+    generator::{self, GeneratorConfig},
+    model::{
+        has_border_walls, Direction, GameMove, GameSpec, GameState, Goal, Point, TargetType,
+        BOARD_SIZE, ROBOT_COUNT, WALL_MAP_SIZE,
+    },
+    serialize,
+    solver::{self, SolveError, SolveStats, Solver},
+};
+
+create_exception!(ricochet_py, UnsolvableError, PyException);
+create_exception!(ricochet_py, GenerateError, PyException);
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+    }
+}
+
+fn parse_direction(name: &str) -> PyResult<Direction> {
+    match name {
+        "up" => Ok(Direction::Up),
+        "down" => Ok(Direction::Down),
+        "left" => Ok(Direction::Left),
+        "right" => Ok(Direction::Right),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown direction {:?}",
+            name
+        ))),
+    }
+}
+
+fn on_board(r: i8, c: i8) -> bool {
+    (0..BOARD_SIZE as i8).contains(&r) && (0..BOARD_SIZE as i8).contains(&c)
+}
+
+fn copy_spec(spec: &GameSpec) -> GameSpec {
+    GameSpec::new(spec.walls, spec.goals.clone())
+}
+
+#[pyclass(name = "GameSpec", module = "ricochet_py", frozen)]
+struct PyGameSpec {
+    spec: GameSpec,
+}
+
+#[pymethods]
+impl PyGameSpec {
+    // `walls` is the 33x33 wall map: cell (r, c) sits at (2r+1, 2c+1) and the
+    // walls around it at the neighbouring entries. `goals` holds
+    // (row, column, robot) with robot None for the wild target.
+    #[new]
+    fn new(walls: Vec<Vec<bool>>, goals: Vec<(i8, i8, Option<usize>)>) -> PyResult<PyGameSpec> {
+        if walls.len() != WALL_MAP_SIZE || walls.iter().any(|row| row.len() != WALL_MAP_SIZE) {
+            return Err(PyValueError::new_err(format!(
+                "walls must be {0}x{0}",
+                WALL_MAP_SIZE
+            )));
+        }
+        let mut wall_map = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+        for (row, values) in wall_map.iter_mut().zip(&walls) {
+            row.copy_from_slice(values);
+        }
+        if !has_border_walls(&wall_map) {
+            return Err(PyValueError::new_err(
+                "walls must close the border of the board",
+            ));
+        }
+        if goals.is_empty() {
+            return Err(PyValueError::new_err("a spec needs at least one goal"));
+        }
+        let goals = goals
+            .into_iter()
+            .map(|(r, c, robot)| {
+                if !on_board(r, c) {
+                    return Err(PyValueError::new_err(format!(
+                        "goal ({}, {}) is outside the board",
+                        r, c
+                    )));
+                }
+                let target_type = match robot {
+                    None => TargetType::Any,
+                    Some(robot) if robot < ROBOT_COUNT => TargetType::Particular(robot),
+                    Some(robot) => {
+                        return Err(PyValueError::new_err(format!("unknown robot {}", robot)))
+                    }
+                };
+                Ok(Goal {
+                    position: Point::new(r, c),
+                    target_type,
+                })
+            })
+            .collect::<PyResult<Vec<Goal>>>()?;
+        Ok(PyGameSpec {
+            spec: GameSpec::new(wall_map, goals),
+        })
+    }
+
+    #[getter]
+    fn walls(&self) -> Vec<Vec<bool>> {
+        self.spec.walls.iter().map(|row| row.to_vec()).collect()
+    }
+
+    #[getter]
+    fn goals(&self) -> Vec<(i8, i8, Option<usize>)> {
+        self.spec
+            .goals
+            .iter()
+            .map(|goal| {
+                let robot = match goal.target_type {
+                    TargetType::Any => None,
+                    TargetType::Particular(robot) => Some(robot),
+                };
+                (goal.position.r, goal.position.c, robot)
+            })
+            .collect()
+    }
+
+    fn has_wall(&self, r: i8, c: i8, direction: &str) -> PyResult<bool> {
+        if !on_board(r, c) {
+            return Err(PyValueError::new_err("the cell is outside the board"));
+        }
+        Ok(self
+            .spec
+            .has_wall(Point::new(r, c), parse_direction(direction)?))
+    }
+
+    fn is_winning_state(&self, state: &PyGameState) -> bool {
+        self.spec.is_winning_state(&state.state)
+    }
+
+    // the state after every move, robot by robot: up, down, left, right.
+    fn next_states(&self, state: &PyGameState) -> Vec<PyGameState> {
+        self.spec
+            .next_states(&state.state)
+            .into_iter()
+            .map(|state| PyGameState { state })
+            .collect()
+    }
+
+    fn apply_move(&self, state: &PyGameState, robot: u8, direction: &str) -> PyResult<PyGameState> {
+        if robot as usize >= ROBOT_COUNT {
+            return Err(PyValueError::new_err(format!("unknown robot {}", robot)));
+        }
+        let game_move = GameMove {
+            robot_index: robot,
+            direction: parse_direction(direction)?,
+        };
+        Ok(PyGameState {
+            state: self.spec.apply_move(&state.state, &game_move),
+        })
+    }
+
+    fn walls_array<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<bool>>> {
+        Ok(PyArray2::from_vec2(py, &self.walls())?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("GameSpec(goals={:?})", self.goals())
+    }
+}
+
+#[pyclass(name = "GameState", module = "ricochet_py", frozen, eq)]
+#[derive(Clone, PartialEq)]
+struct PyGameState {
+    state: GameState,
+}
+
+#[pymethods]
+impl PyGameState {
+    // `robots` holds the (row, column) of every robot.
+    #[new]
+    fn new(robots: Vec<(i8, i8)>) -> PyResult<PyGameState> {
+        if robots.len() != ROBOT_COUNT {
+            return Err(PyValueError::new_err(format!(
+                "a state has {} robots",
+                ROBOT_COUNT
+            )));
+        }
+        let robots: Vec<Point> = robots.into_iter().map(|(r, c)| Point::new(r, c)).collect();
+        for (i, robot) in robots.iter().enumerate() {
+            if !on_board(robot.r, robot.c) {
+                return Err(PyValueError::new_err(format!(
+                    "robot {} is outside the board",
+                    i
+                )));
+            }
+            if robots[..i].contains(robot) {
+                return Err(PyValueError::new_err(format!(
+                    "robot {} shares a cell with another robot",
+                    i
+                )));
+            }
+        }
+        Ok(PyGameState {
+            state: GameState {
+                robots: std::array::from_fn(|i| robots[i]),
+            },
+        })
+    }
+
+    #[getter]
+    fn robots(&self) -> Vec<(i8, i8)> {
+        self.state
+            .robots
+            .iter()
+            .map(|robot| (robot.r, robot.c))
+            .collect()
+    }
+
+    fn robots_array<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<i8>>> {
+        let rows: Vec<Vec<i8>> = self.robots().into_iter().map(|(r, c)| vec![r, c]).collect();
+        Ok(PyArray2::from_vec2(py, &rows)?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("GameState(robots={:?})", self.robots())
+    }
+}
+
+#[pyclass(name = "Solution", module = "ricochet_py", frozen)]
+struct PySolution {
+    moves: Vec<GameMove>,
+    // None for the plain bfs, which doesn't count its states
+    #[pyo3(get)]
+    explored_states: Option<usize>,
+    // seconds
+    #[pyo3(get)]
+    elapsed: f64,
+}
+
+#[pymethods]
+impl PySolution {
+    // (robot, direction) pairs
+    #[getter]
+    fn moves(&self) -> Vec<(u8, &'static str)> {
+        self.moves
+            .iter()
+            .map(|game_move| (game_move.robot_index, direction_name(game_move.direction)))
+            .collect()
+    }
+
+    // one row per move: robot index, then the direction as 0 up, 1 down, 2 left, 3 right.
+    fn moves_array<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u8>> {
+        let flat: Vec<u8> = self
+            .moves
+            .iter()
+            .flat_map(|game_move| [game_move.robot_index, game_move.direction as u8])
+            .collect();
+        PyArray1::from_vec(py, flat)
+            .reshape([self.moves.len(), 2])
+            .expect("two entries per move")
+    }
+
+    fn __len__(&self) -> usize {
+        self.moves.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Solution(moves={:?})", self.moves())
+    }
+}
+
+fn solve_error(error: SolveError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        SolveError::Unsolvable(_) | SolveError::NoSolution(_) => UnsolvableError::new_err(message),
+        SolveError::TimedOut(_) => PyTimeoutError::new_err(message),
+        SolveError::StateLimit(_) => PyMemoryError::new_err(message),
+    }
+}
+
+fn solution(moves: Vec<GameMove>, stats: Option<SolveStats>, elapsed: Duration) -> PySolution {
+    PySolution {
+        moves,
+        explored_states: stats.map(|stats| stats.explored_states),
+        elapsed: elapsed.as_secs_f64(),
+    }
+}
+
+// a solver that keeps its 512MB visited set between solves.
+#[pyclass(name = "Solver", module = "ricochet_py")]
+struct PySolver {
+    solver: Solver,
+}
+
+#[pymethods]
+impl PySolver {
+    #[new]
+    #[pyo3(signature = (state_limit=None))]
+    fn new(state_limit: Option<usize>) -> PySolver {
+        let mut solver = Solver::new();
+        solver.set_state_limit(state_limit);
+        PySolver { solver }
+    }
+
+    // raises UnsolvableError, TimeoutError after `timeout` seconds, or
+    // MemoryError at the state limit.
+    #[pyo3(signature = (spec, state, timeout=None))]
+    fn solve(
+        &mut self,
+        py: Python,
+        spec: &PyGameSpec,
+        state: &PyGameState,
+        timeout: Option<f64>,
+    ) -> PyResult<PySolution> {
+        let deadline = timeout.map(|timeout| Instant::now() + Duration::from_secs_f64(timeout));
+        let solver = &mut self.solver;
+        let result = py.detach(|| solver.solve(&spec.spec, &state.state, deadline));
+        let found = result.map_err(solve_error)?;
+        Ok(solution(
+            found.moves,
+            Some(found.stats),
+            found.stats.elapsed,
+        ))
+    }
+}
+
+#[pyfunction]
+fn load(id: &str) -> PyResult<(PyGameSpec, PyGameState)> {
+    let id = id.trim().split("id=").last().unwrap();
+    if !serialize::is_valid_id(id) {
+        return Err(PyValueError::new_err(format!("invalid board id: {}", id)));
+    }
+    let (spec, state) = serialize::load(id);
+    Ok((PyGameSpec { spec }, PyGameState { state }))
+}
+
+#[pyfunction]
+fn dump(spec: &PyGameSpec, state: &PyGameState) -> String {
+    serialize::dump(&spec.spec, &state.state)
+}
+
+// "bfs" is `solve_bfs`; "indexed" allocates a fresh `Solver`, so use a `Solver`
+// object to solve many puzzles or to set a state limit.
+#[pyfunction]
+#[pyo3(signature = (spec, state, algorithm="bfs", timeout=None))]
+fn solve(
+    py: Python,
+    spec: &PyGameSpec,
+    state: &PyGameState,
+    algorithm: &str,
+    timeout: Option<f64>,
+) -> PyResult<PySolution> {
+    match algorithm {
+        "bfs" if timeout.is_some() => {
+            Err(PyValueError::new_err("timeout needs the indexed algorithm"))
+        }
+        "bfs" => {
+            let start = Instant::now();
+            let moves = py.detach(|| solver::solve_bfs(&spec.spec, &state.state));
+            Ok(solution(moves.map_err(solve_error)?, None, start.elapsed()))
+        }
+        "indexed" => PySolver::new(None).solve(py, spec, state, timeout),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown algorithm {:?}",
+            algorithm
+        ))),
+    }
+}
+
+#[pyclass(name = "Puzzle", module = "ricochet_py", frozen)]
+struct PyPuzzle {
+    #[pyo3(get)]
+    seed: u64,
+    // optimal number of moves
+    #[pyo3(get)]
+    moves: usize,
+    // optimal solutions, when the generator counted them
+    #[pyo3(get)]
+    solutions: Option<usize>,
+    spec: GameSpec,
+    state: GameState,
+}
+
+#[pymethods]
+impl PyPuzzle {
+    #[getter]
+    fn spec(&self) -> PyGameSpec {
+        PyGameSpec {
+            spec: copy_spec(&self.spec),
+        }
+    }
+
+    #[getter]
+    fn state(&self) -> PyGameState {
+        PyGameState {
+            state: self.state.clone(),
+        }
+    }
+
+    #[getter]
+    fn id(&self) -> String {
+        serialize::dump(&self.spec, &self.state)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Puzzle(seed={}, moves={})", self.seed, self.moves)
+    }
+}
+
+// takes the flags of the generate binary as keyword arguments:
+// generate(3, moves="4-6", unique=True, goal_spacing=2). raises GenerateError
+// when no puzzle passes within the limits.
+#[pyfunction]
+#[pyo3(signature = (seed, **options))]
+fn generate(py: Python, seed: u64, options: Option<&Bound<PyDict>>) -> PyResult<PyPuzzle> {
+    let mut config = GeneratorConfig::new(seed);
+    for (name, value) in options.into_iter().flatten() {
+        let name = format!("--{}", name.extract::<String>()?.replace('_', "-"));
+        // flags without a value are passed as True, and left out when False
+        let value = match value.extract::<bool>() {
+            Ok(true) => String::new(),
+            Ok(false) => continue,
+            Err(_) => value.str()?.to_string(),
+        };
+        if !config
+            .apply_flag(&name, &value)
+            .map_err(PyValueError::new_err)?
+        {
+            return Err(PyValueError::new_err(format!("unknown option {}", name)));
+        }
+    }
+    let puzzle = py
+        .detach(|| generator::generate(&config))
+        .map_err(|error| match error {
+            generator::GenerateError::InvalidConfig(message) => PyValueError::new_err(message),
+            generator::GenerateError::NoPuzzle(failure) => {
+                GenerateError::new_err(failure.to_string())
+            }
+        })?;
+    Ok(PyPuzzle {
+        seed: puzzle.seed,
+        moves: puzzle.moves,
+        solutions: puzzle.solutions,
+        spec: puzzle.spec,
+        state: puzzle.state,
+    })
+}
+
+#[pymodule]
+fn ricochet_py(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_class::<PyGameSpec>()?;
+    m.add_class::<PyGameState>()?;
+    m.add_class::<PySolution>()?;
+    m.add_class::<PySolver>()?;
+    m.add_class::<PyPuzzle>()?;
+    m.add("UnsolvableError", m.py().get_type::<UnsolvableError>())?;
+    m.add("GenerateError", m.py().get_type::<GenerateError>())?;
+    m.add_function(wrap_pyfunction!(load, m)?)?;
+    m.add_function(wrap_pyfunction!(dump, m)?)?;
+    m.add_function(wrap_pyfunction!(solve, m)?)?;
+    m.add_function(wrap_pyfunction!(generate, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,31 @@
+// runs test_ricochet.py against the extension module cargo built next to this
+// test binary. needs python3 on the PATH; numpy is optional.
+
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs};
+
+#[test]
+fn test_python_module() {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let library = deps.join(format!(
+        "{}ricochet_py{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    ));
+    assert!(library.exists(), "{} was not built", library.display());
+
+    // python imports extension modules by their module name
+    let module_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("python");
+    fs::create_dir_all(&module_dir).unwrap();
+    let suffix = if cfg!(windows) { "pyd" } else { "so" };
+    fs::copy(&library, module_dir.join(format!("ricochet_py.{}", suffix))).unwrap();
+
+    let status = Command::new("python3")
+        .args(["-m", "unittest", "-v", "test_ricochet"])
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests"))
+        .env("PYTHONPATH", &module_dir)
+        .status()
+        .expect("failed to run python3");
+    assert!(status.success());
+}
@@ -0,0 +1,112 @@
+import importlib.util
+import os
+import unittest
+
+import ricochet_py as rr
+
+# a 7 move puzzle from input.txt
+ID = "rKHKNKXIrKM--7--_n---m-----__--Vv------Yv-N-B-_-----Ln-L-m--L-RJk---_XRL--R_-X-g1--j-n--NZ07-XXZf----K--N-BL---Vv----7----X----Zk-----Lj--R__---RLX--n-m------_leXKXAXKVeWDn94ytfICCphpWL97KIP4sVqKkqtUi"
+
+HAS_NUMPY = importlib.util.find_spec("numpy") is not None
+# generating runs a reverse search from every winning placement, which takes
+# about a minute in release builds and much longer in debug ones
+SLOW = os.environ.get("RICOCHET_SLOW_TESTS") == "1"
+
+
+class IdTest(unittest.TestCase):
+    def test_load_dump(self):
+        spec, state = rr.load(ID)
+        self.assertEqual(len(state.robots), 4)
+        self.assertEqual(rr.dump(spec, state), rr.dump(*rr.load(rr.dump(spec, state))))
+        self.assertEqual(rr.load("https://kaseken.github.io/?id=" + ID)[1], state)
+
+    def test_invalid_id(self):
+        with self.assertRaises(ValueError):
+            rr.load("garbage")
+
+
+class ListsTest(unittest.TestCase):
+    def test_build_from_lists(self):
+        spec, state = rr.load(ID)
+        built_spec = rr.GameSpec(spec.walls, spec.goals)
+        built_state = rr.GameState(state.robots)
+        self.assertEqual(built_state, state)
+        self.assertEqual(built_spec.goals, spec.goals)
+        self.assertEqual(rr.dump(built_spec, built_state), rr.dump(spec, state))
+        self.assertEqual(len(spec.next_states(state)), 16)
+
+    def test_invalid_lists(self):
+        spec, state = rr.load(ID)
+        with self.assertRaises(ValueError):
+            rr.GameSpec(spec.walls[1:], spec.goals)
+        open_border = spec.walls
+        open_border[0][1] = False
+        with self.assertRaises(ValueError):
+            rr.GameSpec(open_border, spec.goals)
+        with self.assertRaises(ValueError):
+            rr.load("vK" + ID[2:])
+        with self.assertRaises(ValueError):
+            rr.GameState([(0, 0)] * 4)
+        with self.assertRaises(ValueError):
+            rr.GameState([(0, 0), (0, 1), (0, 2), (16, 0)])
+
+
+class SolveTest(unittest.TestCase):
+    def test_solve(self):
+        spec, state = rr.load(ID)
+        solution = rr.solve(spec, state)
+        self.assertEqual(len(solution), 7)
+        self.assertIsNone(solution.explored_states)
+        for robot, direction in solution.moves:
+            state = spec.apply_move(state, robot, direction)
+        self.assertTrue(spec.is_winning_state(state))
+
+    def test_solver_options(self):
+        spec, state = rr.load(ID)
+        solver = rr.Solver()
+        solution = solver.solve(spec, state)
+        self.assertEqual(len(solution), 7)
+        self.assertGreater(solution.explored_states, 0)
+        with self.assertRaises(TimeoutError):
+            solver.solve(spec, state, timeout=0)
+        with self.assertRaises(MemoryError):
+            rr.Solver(state_limit=100).solve(spec, state)
+        with self.assertRaises(ValueError):
+            rr.solve(spec, state, algorithm="bfs", timeout=1)
+
+    def test_unsolvable(self):
+        spec, state = rr.load(ID)
+        # nothing ever stops in the walled-in center
+        center = rr.GameSpec(spec.walls, [(7, 7, 0)])
+        with self.assertRaises(rr.UnsolvableError):
+            rr.solve(center, state)
+
+
+class GenerateTest(unittest.TestCase):
+    @unittest.skipUnless(SLOW, "set RICOCHET_SLOW_TESTS=1 to generate a puzzle")
+    def test_generate(self):
+        puzzle = rr.generate(3, moves="1-2")
+        self.assertEqual(puzzle.seed, 3)
+        self.assertIn(puzzle.moves, (1, 2))
+        self.assertEqual(len(rr.solve(puzzle.spec, puzzle.state)), puzzle.moves)
+        self.assertEqual(rr.load(puzzle.id)[1], puzzle.state)
+        self.assertEqual(rr.generate(3, moves="1-2").id, puzzle.id)
+
+    def test_invalid_options(self):
+        with self.assertRaises(ValueError):
+            rr.generate(3, colour="red")
+        with self.assertRaises(ValueError):
+            rr.generate(3, moves="many")
+
+
+@unittest.skipUnless(HAS_NUMPY, "numpy is not installed")
+class NumpyTest(unittest.TestCase):
+    def test_arrays(self):
+        spec, state = rr.load(ID)
+        self.assertEqual(spec.walls_array().shape, (33, 33))
+        self.assertEqual(state.robots_array().tolist(), [list(robot) for robot in state.robots])
+        self.assertEqual(rr.solve(spec, state).moves_array().shape, (7, 2))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -11,11 +11,8 @@ pub fn permutation_swaps(n: usize) -> Vec<usize> {
     let perms = permutations(n);
     let mut swap_position = vec![];
     for i in 0..(perms.len() - 1) {
-        for pos in 0..perms[i].len() {
-            if perms[i][pos] != perms[i + 1][pos] {
-                swap_position.push(pos);
-                break;
-            }
+        if let Some(pos) = (0..perms[i].len()).find(|&pos| perms[i][pos] != perms[i + 1][pos]) {
+            swap_position.push(pos);
         }
     }
     swap_position
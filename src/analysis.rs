@@ -0,0 +1,268 @@
+use std::fmt;
+
+use crate::algorithm::factorial;
+use crate::generator;
+use crate::model::{GameSpec, GameState, Goal, Point, BOARD_SIZE, DIRECTIONS, ROBOT_COUNT};
+
+pub type CellSet = [[bool; BOARD_SIZE]; BOARD_SIZE];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Census {
+    // depths[d] is the number of robot placements whose optimal solution has d moves.
+    pub depths: Vec<u64>,
+    pub unsolvable: u64,
+}
+
+impl Census {
+    pub fn solvable(&self) -> u64 {
+        self.depths.iter().sum()
+    }
+
+    // None when no placement is solvable.
+    pub fn mean(&self) -> Option<f64> {
+        if self.solvable() == 0 {
+            return None;
+        }
+        let total: u64 = self
+            .depths
+            .iter()
+            .enumerate()
+            .map(|(depth, count)| depth as u64 * count)
+            .sum();
+        Some(total as f64 / self.solvable() as f64)
+    }
+
+    // None when no placement is solvable.
+    pub fn median(&self) -> Option<usize> {
+        let half = self.solvable().div_ceil(2);
+        let mut seen = 0;
+        for (depth, count) in self.depths.iter().enumerate() {
+            seen += count;
+            if seen >= half && seen > 0 {
+                return Some(depth);
+            }
+        }
+        None
+    }
+}
+
+// a histogram with one bar per move count, then the unsolvable count and averages.
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let max = self.depths.iter().copied().max().unwrap_or(0).max(1);
+        for (depth, count) in self.depths.iter().enumerate() {
+            let bar = "#".repeat((count * 50).div_ceil(max) as usize);
+            writeln!(f, "{:>3} moves: {:>12} {}", depth, count, bar)?;
+        }
+        writeln!(f, "unsolvable: {}", self.unsolvable)?;
+        match (self.mean(), self.median()) {
+            (Some(mean), Some(median)) => {
+                write!(f, "mean {:.2} moves, median {} moves", mean, median)
+            }
+            _ => write!(f, "no placement is solvable"),
+        }
+    }
+}
+
+// counts every placement of the robots on distinct open cells by its optimal move count.
+pub fn census(spec: &GameSpec, goal: &Goal) -> Census {
+    let spec = GameSpec::new(spec.walls, vec![goal.clone()]);
+    // each visited state stands for all orders of the robots other than the fixed one.
+    let class_size = factorial(ROBOT_COUNT - 1) as u64;
+    let mut depths: Vec<u64> = Vec::new();
+    generator::reverse_search(&spec, |_, depth| {
+        if depths.len() <= depth {
+            depths.resize(depth + 1, 0);
+        }
+        depths[depth] += class_size;
+        true
+    });
+
+    let cells = open_cells(&spec, goal) as u64;
+    let placements: u64 = (0..ROBOT_COUNT as u64).map(|i| cells - i).product();
+    let solvable: u64 = depths.iter().sum();
+    Census {
+        depths,
+        unsolvable: placements - solvable,
+    }
+}
+
+// cells connected to the goal when robots are ignored. this leaves out cells walled
+// off from the rest of the board, like the center of a standard board: robots never
+// get in or out of them, so no placement the search visits uses them.
+fn open_cells(spec: &GameSpec, goal: &Goal) -> usize {
+    let mut cells: CellSet = [[false; BOARD_SIZE]; BOARD_SIZE];
+    cells[goal.position.r as usize][goal.position.c as usize] = true;
+    let mut stack = vec![goal.position];
+    while let Some(position) = stack.pop() {
+        for direction in DIRECTIONS {
+            if spec.has_wall(position, direction) {
+                continue;
+            }
+            let next = position + Point::from(direction);
+            if !cells[next.r as usize][next.c as usize] {
+                cells[next.r as usize][next.c as usize] = true;
+                stack.push(next);
+            }
+        }
+    }
+    cells.iter().flatten().filter(|&&cell| cell).count()
+}
+
+// cells where a robot can come to rest, given where the robots start.
+// a robot stops in front of a wall, or in front of a robot that could be there;
+// the result is a superset of the cells that are actually reachable.
+pub fn stoppable_cells(spec: &GameSpec, robots: &[Point]) -> CellSet {
+    let mut cells: CellSet = [[false; BOARD_SIZE]; BOARD_SIZE];
+    for robot in robots {
+        cells[robot.r as usize][robot.c as usize] = true;
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for r in 0..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                if cells[r][c] {
+                    continue;
+                }
+                let position = Point::new(r as i8, c as i8);
+                let stops = DIRECTIONS.iter().any(|&direction| {
+                    let can_arrive = !spec.has_wall(position, direction.reverse());
+                    let blocked = spec.has_wall(position, direction) || {
+                        let next = position + Point::from(direction);
+                        cells[next.r as usize][next.c as usize]
+                    };
+                    can_arrive && blocked
+                });
+                if stops {
+                    cells[r][c] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+    cells
+}
+
+// cells no robot can move onto and stop on. the search starts without robots, so
+// this only holds for placements that keep every robot off these cells: a robot
+// starting on one rests there, and can be a blocker for its neighbours.
+pub fn dead_cells(spec: &GameSpec) -> CellSet {
+    let stoppable = stoppable_cells(spec, &[]);
+    let mut cells: CellSet = [[false; BOARD_SIZE]; BOARD_SIZE];
+    for r in 0..BOARD_SIZE {
+        for c in 0..BOARD_SIZE {
+            cells[r][c] = !stoppable[r][c];
+        }
+    }
+    cells
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsolvable {
+    pub goal: Goal,
+}
+
+impl fmt::Display for Unsolvable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unsolvable: no robot can stop on the goal at ({}, {})",
+            self.goal.position.r, self.goal.position.c
+        )
+    }
+}
+
+// cheap check that every goal can be reached at all, before any search.
+pub fn check_solvable(spec: &GameSpec, state: &GameState) -> Result<(), Unsolvable> {
+    let stoppable = stoppable_cells(spec, &state.robots);
+    for goal in &spec.goals {
+        let position = goal.position;
+        if !goal.is_reached(state) && !stoppable[position.r as usize][position.c as usize] {
+            return Err(Unsolvable { goal: goal.clone() });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{small_spec, ID};
+    use crate::model::TargetType;
+    use crate::serialize;
+
+    #[test]
+    fn test_dead_cells() {
+        let (spec, state) = serialize::load(ID);
+        assert!(check_solvable(&spec, &state).is_ok());
+
+        // only the walled-in center can never be stopped on.
+        let dead = dead_cells(&spec);
+        for (r, row) in dead.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                assert_eq!(*cell, (7..=8).contains(&r) && (7..=8).contains(&c));
+            }
+        }
+
+        let goal = Goal {
+            position: Point::new(7, 8),
+            target_type: TargetType::Particular(0),
+        };
+        let spec = GameSpec::new(spec.walls, vec![goal.clone()]);
+        assert_eq!(check_solvable(&spec, &state), Err(Unsolvable { goal }));
+    }
+
+    #[test]
+    fn test_census_statistics() {
+        let census = Census {
+            depths: vec![0, 2, 3, 5],
+            unsolvable: 7,
+        };
+        assert_eq!(census.solvable(), 10);
+        assert_eq!(census.mean(), Some(2.3));
+        assert_eq!(census.median(), Some(2));
+
+        let unsolvable = Census {
+            depths: Vec::new(),
+            unsolvable: 7,
+        };
+        assert_eq!(unsolvable.mean(), None);
+        assert_eq!(unsolvable.median(), None);
+        assert!(unsolvable.to_string().ends_with("no placement is solvable"));
+    }
+
+    #[test]
+    fn test_census_small_board() {
+        let goal = Goal {
+            position: Point::new(1, 1),
+            target_type: TargetType::Particular(0),
+        };
+        let census = census(&small_spec(goal.clone()), &goal);
+        // four robots on the nine open cells, in every order
+        assert_eq!(census.solvable() + census.unsolvable, 9 * 8 * 7 * 6);
+        // red on the goal and the others anywhere
+        assert_eq!(census.depths[0], 8 * 7 * 6);
+        assert!(census.mean().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_open_cells() {
+        let (spec, _) = serialize::load(ID);
+        // every cell but the walled-in center
+        assert_eq!(open_cells(&spec, &spec.goals[0]), 16 * 16 - 4);
+
+        // a center walled in as a group is still left out
+        let mut walls = spec.walls;
+        for (r, c) in [(15, 16), (17, 16), (16, 15), (16, 17), (16, 16)] {
+            walls[r][c] = false;
+        }
+        let spec = GameSpec::new(walls, spec.goals);
+        assert_eq!(open_cells(&spec, &spec.goals[0]), 16 * 16 - 4);
+        let center = Goal {
+            position: Point::new(7, 7),
+            target_type: TargetType::Any,
+        };
+        assert_eq!(open_cells(&spec, &center), 4);
+    }
+}
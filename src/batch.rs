@@ -0,0 +1,187 @@
+// solves many puzzles on a pool of worker threads. every worker keeps one
+// `Solver` for the life of the `BatchSolver`, so the 512MB visited set is
+// allocated once per worker rather than once per puzzle. the memory limit caps
+// both the number of workers and how many states each of them may keep.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use web_time::{Duration, Instant};
+
+use crate::model::{GameSpec, GameState};
+use crate::solver::{Solution, SolveError, Solver, STATE_BYTES, VISITED_SET_BYTES};
+
+// a worker needs its visited set and at least as much again for states.
+pub const MIN_WORKER_BYTES: usize = 2 * VISITED_SET_BYTES;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchConfig {
+    pub threads: usize,
+    // bytes for all workers together
+    pub memory_limit: usize,
+    // per puzzle
+    pub timeout: Option<Duration>,
+}
+
+impl Default for BatchConfig {
+    fn default() -> BatchConfig {
+        BatchConfig {
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            memory_limit: 4 << 30,
+            timeout: None,
+        }
+    }
+}
+
+pub struct BatchSolver {
+    config: BatchConfig,
+    workers: usize,
+    state_limit: usize,
+    // allocated as batches need them, at most `workers`
+    solvers: Vec<Solver>,
+}
+
+impl BatchSolver {
+    pub fn new(config: BatchConfig) -> Result<BatchSolver, String> {
+        if config.threads == 0 {
+            return Err("a batch needs at least one thread".to_string());
+        }
+        let workers = config.threads.min(config.memory_limit / MIN_WORKER_BYTES);
+        if workers == 0 {
+            return Err(format!(
+                "a worker needs at least {}MB of memory",
+                MIN_WORKER_BYTES >> 20
+            ));
+        }
+        let state_limit = (config.memory_limit / workers - VISITED_SET_BYTES) / STATE_BYTES;
+        Ok(BatchSolver {
+            config,
+            workers,
+            state_limit,
+            solvers: Vec::new(),
+        })
+    }
+
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    // the most states one solve may keep before giving up with `StateLimit`.
+    pub fn state_limit(&self) -> usize {
+        self.state_limit
+    }
+
+    // the results in the order of the puzzles.
+    pub fn solve(
+        &mut self,
+        puzzles: &[(GameSpec, GameState)],
+    ) -> Vec<Result<Solution, SolveError>> {
+        let mut results: Vec<_> = puzzles.iter().map(|_| None).collect();
+        self.solve_each(puzzles, |index, result| results[index] = Some(result));
+        results.into_iter().map(Option::unwrap).collect()
+    }
+
+    // calls `on_result` on the calling thread as each puzzle is solved, in
+    // whatever order the workers finish them.
+    pub fn solve_each(
+        &mut self,
+        puzzles: &[(GameSpec, GameState)],
+        mut on_result: impl FnMut(usize, Result<Solution, SolveError>),
+    ) {
+        let workers = self.workers.min(puzzles.len());
+        while self.solvers.len() < workers {
+            let mut solver = Solver::new();
+            solver.set_state_limit(Some(self.state_limit));
+            self.solvers.push(solver);
+        }
+
+        let timeout = self.config.timeout;
+        let next_puzzle = AtomicUsize::new(0);
+        let (send, results) = mpsc::channel();
+        thread::scope(|scope| {
+            for solver in &mut self.solvers[..workers] {
+                let send = send.clone();
+                let next_puzzle = &next_puzzle;
+                scope.spawn(move || loop {
+                    let index = next_puzzle.fetch_add(1, Ordering::Relaxed);
+                    let Some((spec, state)) = puzzles.get(index) else {
+                        break;
+                    };
+                    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+                    let result = solver.solve(spec, state, deadline);
+                    if send.send((index, result)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(send);
+            for (index, result) in results {
+                on_result(index, result);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::EASY_ID;
+    use crate::serialize;
+    use crate::solver::solve_bfs;
+
+    #[test]
+    fn test_memory_limit() {
+        let config = |threads, memory_limit| BatchConfig {
+            threads,
+            memory_limit,
+            timeout: None,
+        };
+        assert!(BatchSolver::new(config(4, MIN_WORKER_BYTES - 1)).is_err());
+        assert!(BatchSolver::new(config(0, MIN_WORKER_BYTES)).is_err());
+
+        let batch = BatchSolver::new(config(4, 3 * MIN_WORKER_BYTES)).unwrap();
+        assert_eq!(batch.workers(), 3);
+        let batch = BatchSolver::new(config(2, 3 * MIN_WORKER_BYTES)).unwrap();
+        assert_eq!(batch.workers(), 2);
+        let worker_bytes = VISITED_SET_BYTES + batch.state_limit() * STATE_BYTES;
+        assert!(worker_bytes * 2 <= 3 * MIN_WORKER_BYTES);
+    }
+
+    #[test]
+    fn test_batch_keeps_order() {
+        let (spec, state) = serialize::load(EASY_ID);
+        let moves = solve_bfs(&spec, &state).unwrap();
+        // the puzzle and the states along its solution, last to first
+        let mut states = vec![state];
+        for game_move in &moves {
+            states.push(spec.apply_move(states.last().unwrap(), game_move));
+        }
+        let puzzles: Vec<(GameSpec, GameState)> = states
+            .into_iter()
+            .rev()
+            .map(|state| (GameSpec::new(spec.walls, spec.goals.clone()), state))
+            .collect();
+        let mut batch = BatchSolver::new(BatchConfig {
+            threads: 2,
+            memory_limit: 2 * MIN_WORKER_BYTES,
+            timeout: None,
+        })
+        .unwrap();
+
+        let results = batch.solve(&puzzles[..2]);
+        let results = results.into_iter().chain(batch.solve(&puzzles[2..]));
+        for (distance, result) in results.enumerate() {
+            assert_eq!(result.unwrap().moves.len(), distance);
+        }
+        assert_eq!(batch.solvers.len(), 2);
+
+        let solver = &mut batch.solvers[0];
+        solver.set_state_limit(Some(100));
+        let (spec, state) = &puzzles[moves.len()];
+        match solver.solve(spec, state, None) {
+            Err(SolveError::StateLimit(stats)) => assert_eq!(stats.explored_states, 100),
+            result => panic!("expected the state limit, got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,585 @@
+// the bidding phase of a round. players call move counts while the timer runs,
+// then demonstrate from the lowest bid up until one of them succeeds.
+// time only moves on with `Event::Tick`, so rounds replay deterministically.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::game::{self, Game};
+use crate::model::GameMove;
+
+#[derive(Debug, Clone)]
+pub struct BiddingRules {
+    // seconds from the first bid until bidding closes
+    pub timer_seconds: u64,
+    // a demonstration with more moves than bid costs the player a chip
+    pub overbid_costs_chip: bool,
+    // seconds without any bid until the round is lost; None waits forever
+    pub idle_seconds: Option<u64>,
+    // seconds each demonstrator has before their demonstration fails; None waits forever
+    pub demonstration_seconds: Option<u64>,
+}
+
+impl Default for BiddingRules {
+    fn default() -> BiddingRules {
+        BiddingRules {
+            timer_seconds: 60,
+            overbid_costs_chip: true,
+            idle_seconds: Some(300),
+            demonstration_seconds: Some(60),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Bid { player: usize, moves: usize },
+    Demonstrate { player: usize, moves: Vec<GameMove> },
+    Tick { seconds: u64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Outcome {
+    TimerStarted { deadline: u64 },
+    BidAccepted { player: usize, moves: usize },
+    // bidders in demonstration order
+    BiddingClosed { order: Vec<usize> },
+    DemonstrationFailed { player: usize, penalized: bool },
+    NextDemonstrator { player: usize },
+    RoundWon { player: usize, moves: usize },
+    // nobody bid, or every demonstration failed
+    RoundLost,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BiddingError {
+    NoTarget,
+    UnknownPlayer(usize),
+    BiddingClosed,
+    BiddingOpen,
+    // a player can only lower their bid
+    BidNotLower { current: usize },
+    NotYourTurn { expected: usize },
+    RoundOver,
+}
+
+impl fmt::Display for BiddingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BiddingError::NoTarget => write!(f, "no target chip has been drawn"),
+            BiddingError::UnknownPlayer(player) => write!(f, "unknown player {}", player),
+            BiddingError::BiddingClosed => write!(f, "bidding is closed"),
+            BiddingError::BiddingOpen => write!(f, "bidding is still open"),
+            BiddingError::BidNotLower { current } => {
+                write!(f, "bids can only be lowered below {}", current)
+            }
+            BiddingError::NotYourTurn { expected } => {
+                write!(f, "player {} demonstrates now", expected)
+            }
+            BiddingError::RoundOver => write!(f, "the round is over"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bid {
+    player: usize,
+    moves: usize,
+    // when the player last changed the bid
+    time: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Phase {
+    Bidding {
+        deadline: Option<u64>,
+    },
+    Demonstrating {
+        order: Vec<Bid>,
+        index: usize,
+        deadline: Option<u64>,
+    },
+    Over,
+}
+
+#[derive(Debug)]
+pub struct BiddingRound {
+    rules: BiddingRules,
+    now: u64,
+    bids: Vec<Bid>,
+    phase: Phase,
+}
+
+impl BiddingRound {
+    pub fn new(rules: BiddingRules) -> BiddingRound {
+        BiddingRound {
+            rules,
+            now: 0,
+            bids: Vec::new(),
+            phase: Phase::Bidding { deadline: None },
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.phase == Phase::Over
+    }
+
+    // the player who has to demonstrate next, once bidding is closed.
+    pub fn demonstrator(&self) -> Option<usize> {
+        match &self.phase {
+            Phase::Demonstrating { order, index, .. } => Some(order[*index].player),
+            _ => None,
+        }
+    }
+
+    pub fn handle(&mut self, game: &mut Game, event: Event) -> Result<Vec<Outcome>, BiddingError> {
+        // a finished round has already taken or passed its target
+        if self.is_over() {
+            return Err(BiddingError::RoundOver);
+        }
+        if game.current_target().is_none() {
+            return Err(BiddingError::NoTarget);
+        }
+        match event {
+            Event::Tick { seconds } => {
+                self.now += seconds;
+                Ok(self.close_if_due(game))
+            }
+            Event::Bid { player, moves } => self.bid(game, player, moves),
+            Event::Demonstrate { player, moves } => self.demonstrate(game, player, &moves),
+        }
+    }
+
+    fn bid(
+        &mut self,
+        game: &Game,
+        player: usize,
+        moves: usize,
+    ) -> Result<Vec<Outcome>, BiddingError> {
+        if player >= game.chips.len() {
+            return Err(BiddingError::UnknownPlayer(player));
+        }
+        let Phase::Bidding { deadline } = self.phase else {
+            return Err(BiddingError::BiddingClosed);
+        };
+
+        let mut outcomes = Vec::new();
+        match self.bids.iter_mut().find(|bid| bid.player == player) {
+            Some(bid) if bid.moves <= moves => {
+                return Err(BiddingError::BidNotLower { current: bid.moves })
+            }
+            Some(bid) => {
+                bid.moves = moves;
+                bid.time = self.now;
+            }
+            None => self.bids.push(Bid {
+                player,
+                moves,
+                time: self.now,
+            }),
+        }
+        if deadline.is_none() {
+            let deadline = self.now + self.rules.timer_seconds;
+            self.phase = Phase::Bidding {
+                deadline: Some(deadline),
+            };
+            outcomes.push(Outcome::TimerStarted { deadline });
+        }
+        outcomes.push(Outcome::BidAccepted { player, moves });
+        Ok(outcomes)
+    }
+
+    fn close_if_due(&mut self, game: &mut Game) -> Vec<Outcome> {
+        let deadline = match self.phase {
+            Phase::Bidding {
+                deadline: Some(deadline),
+            } => deadline,
+            Phase::Bidding { deadline: None } => return self.give_up_if_idle(game),
+            Phase::Demonstrating {
+                deadline: Some(deadline),
+                ..
+            } if self.now >= deadline => {
+                // the demonstrator ran out of time, or is gone
+                let player = self.demonstrator().unwrap();
+                let mut outcomes = vec![Outcome::DemonstrationFailed {
+                    player,
+                    penalized: false,
+                }];
+                outcomes.extend(self.next_demonstrator(game));
+                return outcomes;
+            }
+            _ => return Vec::new(),
+        };
+        if self.now < deadline {
+            return Vec::new();
+        }
+
+        // lowest bid first; ties go to fewer chips, then to the earlier bid.
+        let mut order = self.bids.clone();
+        order.sort_by_key(|bid| (bid.moves, game.chips[bid.player].len(), bid.time));
+        let players = order.iter().map(|bid| bid.player).collect();
+        let first = order[0].player;
+        self.phase = Phase::Demonstrating {
+            order,
+            index: 0,
+            deadline: self.demonstration_deadline(),
+        };
+        vec![
+            Outcome::BiddingClosed { order: players },
+            Outcome::NextDemonstrator { player: first },
+        ]
+    }
+
+    // nobody has bid yet; the round is lost once the idle time is up.
+    fn give_up_if_idle(&mut self, game: &mut Game) -> Vec<Outcome> {
+        match self.rules.idle_seconds {
+            Some(idle_seconds) if self.now >= idle_seconds => {
+                game.pass_round().unwrap();
+                self.phase = Phase::Over;
+                vec![Outcome::RoundLost]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn demonstrate(
+        &mut self,
+        game: &mut Game,
+        player: usize,
+        moves: &[GameMove],
+    ) -> Result<Vec<Outcome>, BiddingError> {
+        let bid = match &self.phase {
+            Phase::Bidding { .. } => return Err(BiddingError::BiddingOpen),
+            Phase::Over => return Err(BiddingError::RoundOver),
+            Phase::Demonstrating { order, index, .. } => order[*index].clone(),
+        };
+        if bid.player != player {
+            return Err(BiddingError::NotYourTurn {
+                expected: bid.player,
+            });
+        }
+
+        let goal = game.current_target().unwrap();
+        // moves of robots that don't exist fail like any other wrong demonstration
+        let solved = game::verify(&game.spec, &game.state, goal, moves).is_ok();
+        if solved && moves.len() <= bid.moves {
+            game.complete_round(player, moves).unwrap();
+            self.phase = Phase::Over;
+            return Ok(vec![Outcome::RoundWon {
+                player,
+                moves: moves.len(),
+            }]);
+        }
+
+        let penalized = solved && self.rules.overbid_costs_chip && game.return_chip(player);
+        let mut outcomes = vec![Outcome::DemonstrationFailed { player, penalized }];
+        outcomes.extend(self.next_demonstrator(game));
+        Ok(outcomes)
+    }
+
+    // when a demonstration that starts now has to be done.
+    fn demonstration_deadline(&self) -> Option<u64> {
+        self.rules
+            .demonstration_seconds
+            .map(|seconds| self.now + seconds)
+    }
+
+    // the current demonstration failed: the next bidder demonstrates, or the
+    // round is lost when there is none.
+    fn next_demonstrator(&mut self, game: &mut Game) -> Vec<Outcome> {
+        let next_deadline = self.demonstration_deadline();
+        let Phase::Demonstrating {
+            order,
+            index,
+            deadline,
+        } = &mut self.phase
+        else {
+            unreachable!()
+        };
+        *index += 1;
+        if let Some(next) = order.get(*index) {
+            *deadline = next_deadline;
+            return vec![Outcome::NextDemonstrator {
+                player: next.player,
+            }];
+        }
+        game.pass_round().unwrap();
+        self.phase = Phase::Over;
+        vec![Outcome::RoundLost]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{near_goal_state, ID};
+    use crate::solver;
+
+    // a game with a drawn target that is one move away, and its solution.
+    fn setup() -> (Game, Vec<GameMove>) {
+        let mut game = Game::from_id(ID, 3, None);
+        let goal = game.draw().unwrap().clone();
+        game.state = near_goal_state(&game.spec, &game.state, &goal, 1);
+        let moves = solver::solve_bfs(&game.round_spec().unwrap(), &game.state).unwrap();
+        (game, moves)
+    }
+
+    #[test]
+    fn test_lowest_bid_wins() {
+        let (mut game, moves) = setup();
+        let mut round = BiddingRound::new(BiddingRules::default());
+
+        let outcomes = round
+            .handle(
+                &mut game,
+                Event::Bid {
+                    player: 1,
+                    moves: 9,
+                },
+            )
+            .unwrap();
+        assert_eq!(outcomes[0], Outcome::TimerStarted { deadline: 60 });
+        round
+            .handle(&mut game, Event::Tick { seconds: 10 })
+            .unwrap();
+        round
+            .handle(
+                &mut game,
+                Event::Bid {
+                    player: 2,
+                    moves: moves.len(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            round.handle(
+                &mut game,
+                Event::Bid {
+                    player: 1,
+                    moves: 9
+                }
+            ),
+            Err(BiddingError::BidNotLower { current: 9 })
+        );
+        assert_eq!(
+            round.handle(
+                &mut game,
+                Event::Demonstrate {
+                    player: 2,
+                    moves: moves.clone()
+                }
+            ),
+            Err(BiddingError::BiddingOpen)
+        );
+
+        let outcomes = round
+            .handle(&mut game, Event::Tick { seconds: 50 })
+            .unwrap();
+        assert_eq!(outcomes[0], Outcome::BiddingClosed { order: vec![2, 1] });
+        assert_eq!(
+            round.handle(
+                &mut game,
+                Event::Demonstrate {
+                    player: 1,
+                    moves: moves.clone()
+                }
+            ),
+            Err(BiddingError::NotYourTurn { expected: 2 })
+        );
+        let outcomes = round
+            .handle(
+                &mut game,
+                Event::Demonstrate {
+                    player: 2,
+                    moves: moves.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            outcomes,
+            vec![Outcome::RoundWon {
+                player: 2,
+                moves: moves.len()
+            }]
+        );
+        assert!(round.is_over());
+        assert_eq!(game.scores(), vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_failed_demonstrations() {
+        let (mut game, moves) = setup();
+        // player 0 already has a chip, so player 1 demonstrates first on a tie.
+        game.chips[0].push(16);
+        let mut round = BiddingRound::new(BiddingRules::default());
+        let short = moves.len() - 1;
+        round
+            .handle(
+                &mut game,
+                Event::Bid {
+                    player: 0,
+                    moves: short,
+                },
+            )
+            .unwrap();
+        round
+            .handle(
+                &mut game,
+                Event::Bid {
+                    player: 1,
+                    moves: short,
+                },
+            )
+            .unwrap();
+        let outcomes = round
+            .handle(&mut game, Event::Tick { seconds: 60 })
+            .unwrap();
+        assert_eq!(outcomes[0], Outcome::BiddingClosed { order: vec![1, 0] });
+
+        // a wrong demonstration passes to the next bidder without a penalty.
+        let outcomes = round
+            .handle(
+                &mut game,
+                Event::Demonstrate {
+                    player: 1,
+                    moves: Vec::new(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            outcomes,
+            vec![
+                Outcome::DemonstrationFailed {
+                    player: 1,
+                    penalized: false
+                },
+                Outcome::NextDemonstrator { player: 0 },
+            ]
+        );
+
+        // a solution with more moves than bid costs a chip.
+        let outcomes = round
+            .handle(&mut game, Event::Demonstrate { player: 0, moves })
+            .unwrap();
+        assert_eq!(
+            outcomes,
+            vec![
+                Outcome::DemonstrationFailed {
+                    player: 0,
+                    penalized: true
+                },
+                Outcome::RoundLost,
+            ]
+        );
+        assert_eq!(game.scores(), vec![0, 0, 0]);
+        assert!(game.current_target().is_none());
+    }
+
+    #[test]
+    fn test_demonstration_timeout() {
+        let (mut game, moves) = setup();
+        let mut round = BiddingRound::new(BiddingRules::default());
+        for player in [0, 1] {
+            let bid = Event::Bid {
+                player,
+                moves: moves.len() + player,
+            };
+            round.handle(&mut game, bid).unwrap();
+        }
+        round
+            .handle(&mut game, Event::Tick { seconds: 60 })
+            .unwrap();
+        assert_eq!(round.demonstrator(), Some(0));
+
+        // player 0 never answers
+        let outcomes = round
+            .handle(&mut game, Event::Tick { seconds: 59 })
+            .unwrap();
+        assert!(outcomes.is_empty());
+        let outcomes = round.handle(&mut game, Event::Tick { seconds: 1 }).unwrap();
+        assert_eq!(
+            outcomes,
+            vec![
+                Outcome::DemonstrationFailed {
+                    player: 0,
+                    penalized: false
+                },
+                Outcome::NextDemonstrator { player: 1 },
+            ]
+        );
+
+        // a robot the board doesn't have fails the demonstration
+        let unknown_robot = GameMove {
+            robot_index: 7,
+            direction: moves[0].direction,
+        };
+        let outcomes = round
+            .handle(
+                &mut game,
+                Event::Demonstrate {
+                    player: 1,
+                    moves: vec![unknown_robot],
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            outcomes,
+            vec![
+                Outcome::DemonstrationFailed {
+                    player: 1,
+                    penalized: false
+                },
+                Outcome::RoundLost,
+            ]
+        );
+        assert_eq!(game.scores(), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_nobody_bids() {
+        let (mut game, _) = setup();
+        let mut round = BiddingRound::new(BiddingRules::default());
+        let outcomes = round
+            .handle(&mut game, Event::Tick { seconds: 299 })
+            .unwrap();
+        assert!(outcomes.is_empty());
+        let outcomes = round.handle(&mut game, Event::Tick { seconds: 1 }).unwrap();
+        assert_eq!(outcomes, vec![Outcome::RoundLost]);
+        assert!(round.is_over());
+        assert_eq!(game.scores(), vec![0, 0, 0]);
+        assert!(game.current_target().is_none());
+
+        // the target is gone with the round, which still reports that it is over
+        assert_eq!(
+            round.handle(
+                &mut game,
+                Event::Bid {
+                    player: 0,
+                    moves: 3
+                }
+            ),
+            Err(BiddingError::RoundOver)
+        );
+        assert_eq!(
+            round.handle(&mut game, Event::Tick { seconds: 1 }),
+            Err(BiddingError::RoundOver)
+        );
+    }
+
+    #[test]
+    fn test_no_idle_limit() {
+        let (mut game, _) = setup();
+        let rules = BiddingRules {
+            idle_seconds: None,
+            ..BiddingRules::default()
+        };
+        let mut round = BiddingRound::new(rules);
+        let outcomes = round
+            .handle(&mut game, Event::Tick { seconds: 10_000 })
+            .unwrap();
+        assert!(outcomes.is_empty());
+        assert!(!round.is_over());
+    }
+}
@@ -0,0 +1,12 @@
+use std::{env, io, process};
+
+use ricochet_robots::cli;
+
+// `ricochet analyze` on a seeded board or the lines of stdin.
+fn main() {
+    let args: Vec<String> = ["analyze".to_string()]
+        .into_iter()
+        .chain(env::args().skip(1))
+        .collect();
+    process::exit(cli::run(&args, &mut io::stdout().lock()));
+}
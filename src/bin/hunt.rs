@@ -0,0 +1,91 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::{env, process, thread};
+
+use ricochet_robots::{
+    generator::GeneratorConfig,
+    hunt::{self, Hunt},
+    serialize,
+};
+
+fn main() {
+    let (flags, args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let checkpoint = args.first().expect("usage: hunt <checkpoint> [--threads=N] [--first-seed=N] [--top=N] [--jobs=N]");
+    let checkpoint = Path::new(checkpoint);
+
+    let mut threads = 1;
+    let mut first_seed = None;
+    let mut top = None;
+    let mut jobs = u64::MAX;
+    for flag in &flags {
+        let (name, value) = flag.split_once('=').unwrap_or((flag, ""));
+        let value: u64 = value.parse().expect("flag values must be numbers");
+        match name {
+            "--threads" => threads = value,
+            "--first-seed" => first_seed = Some(value),
+            "--top" => top = Some(value as usize),
+            "--jobs" => jobs = value,
+            _ => panic!("unknown flag {}", flag),
+        }
+    }
+
+    let goals = GeneratorConfig::new(first_seed.unwrap_or(0)).rules.goal_count;
+    let hunt = if checkpoint.exists() {
+        let hunt = Hunt::load(checkpoint).expect("failed to load checkpoint");
+        // flags left out take the checkpoint's values
+        let given = Hunt::new(
+            first_seed.unwrap_or(hunt.first_seed),
+            goals,
+            top.unwrap_or(hunt.leaderboard_size),
+        );
+        if let Err(message) = hunt.check_settings(&given) {
+            eprintln!("refusing to resume: {}", message);
+            process::exit(1);
+        }
+        println!("resuming from {} finished jobs", hunt.finished_jobs());
+        hunt
+    } else {
+        Hunt::new(first_seed.unwrap_or(0), goals, top.unwrap_or(20))
+    };
+    let hunt = Mutex::new(hunt);
+    let taken = Mutex::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let (seed, goal) = {
+                    let mut taken = taken.lock().unwrap();
+                    if *taken >= jobs {
+                        return;
+                    }
+                    *taken += 1;
+                    hunt.lock().unwrap().take_job()
+                };
+                let record = hunt::run_job(&GeneratorConfig::new(seed), goal);
+
+                let mut hunt = hunt.lock().unwrap();
+                match &record {
+                    Some(record) => println!("seed {} goal {}: {} moves", seed, goal, record.moves),
+                    None => println!("seed {} goal {}: no acceptable placement", seed, goal),
+                }
+                let url = record.as_ref().map(|record| serialize::to_url(&record.id));
+                // the job came from take_job, so it belongs to the hunt
+                if hunt.finish_job(seed, goal, record).unwrap() {
+                    println!("new leaderboard entry: {}", url.unwrap());
+                }
+                hunt.save(checkpoint).expect("failed to save checkpoint");
+            });
+        }
+    });
+
+    for record in &hunt.lock().unwrap().leaderboard {
+        println!(
+            "{} moves (seed {} goal {}) {}",
+            record.moves,
+            record.seed,
+            record.goal,
+            serialize::to_url(&record.id)
+        );
+    }
+}
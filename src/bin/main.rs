@@ -1,26 +1,15 @@
-use std::io::{self, BufRead};
+use std::{env, io, process};
 
-use ricochet_robots::{serialize, solver};
+use ricochet_robots::cli;
 
+// `ricochet solve` on the lines of stdin; `--jsonl` is `--output=jsonl`.
 fn main() {
-    let stdin = io::stdin();
-    for line in stdin.lock().lines() {
-        let line = line.unwrap();
-        let board = if line.starts_with("http") {
-            line.split("id=").nth(1).unwrap()
-        } else {
-            &line
-        };
-        let (spec, state) = serialize::load(&board);
-
-        let result = solver::solve_bfs(&spec, &state);
-        println!("found a solution with {} moves", result.len());
-        for game_move in result {
-            println!(
-                "> Move {} to {:?} ",
-                serialize::robot_index_to_color(game_move.robot_index),
-                game_move.direction
-            )
-        }
-    }
+    let args: Vec<String> = ["solve".to_string()]
+        .into_iter()
+        .chain(env::args().skip(1).map(|arg| match arg.as_str() {
+            "--jsonl" => "--output=jsonl".to_string(),
+            _ => arg,
+        }))
+        .collect();
+    process::exit(cli::run(&args, &mut io::stdout().lock()));
 }
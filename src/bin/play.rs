@@ -0,0 +1,232 @@
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+use std::{env, fs, thread};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::{Color, Print, PrintStyledContent, Stylize};
+use crossterm::{cursor, execute, queue, terminal};
+use rand::{self, Rng};
+use ricochet_robots::{
+    generator::{self, GeneratorConfig},
+    json,
+    model::{Direction, GameMove, GameSpec, GameState, TargetType},
+    play::{self, Session},
+    render, serialize, solver,
+};
+
+const ROBOT_KEYS: [char; 4] = ['r', 'b', 'g', 'y'];
+const ROBOT_COLORS: [Color; 4] = [Color::Red, Color::Blue, Color::Green, Color::Yellow];
+const ANIMATION_STEP: Duration = Duration::from_millis(400);
+
+enum Reveal {
+    // None if the puzzle has no solution
+    Solving(Receiver<Option<Vec<GameMove>>>),
+    // moves still to animate, and when the last one was shown
+    Animating(Vec<GameMove>, Instant),
+    // the optimal move count, None if there is no solution
+    Done(Option<usize>),
+}
+
+// raw mode and the alternate screen for as long as it lives, so the terminal is
+// restored on errors and panics too.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter(out: &mut impl Write) -> io::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        let guard = RawTerminal;
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// a kaseken id or url, `--seed=N [--goal=K]`, or `--file=path` with an id or a json puzzle.
+fn load() -> (GameSpec, GameState) {
+    let mut seed = None;
+    let mut goal = 0;
+    let mut file = None;
+    let mut id = None;
+    for arg in env::args().skip(1) {
+        let (name, value) = arg.split_once('=').unwrap_or((&arg, ""));
+        match name {
+            "--seed" => seed = Some(value.parse().expect("seed must be a u64")),
+            "--goal" => goal = value.parse().expect("goal must be a number"),
+            "--file" => file = Some(value.to_string()),
+            _ if !arg.starts_with("--") => id = Some(arg.split("id=").last().unwrap().to_string()),
+            _ => panic!("unknown flag {}", arg),
+        }
+    }
+
+    if let Some(path) = file {
+        let text = fs::read_to_string(&path).expect("failed to read the file");
+        let text = text.trim();
+        if text.starts_with('{') {
+            let puzzle: json::Puzzle = serde_json::from_str(text).expect("invalid json puzzle");
+            return puzzle.to_game().expect("invalid json puzzle");
+        }
+        id = Some(text.split("id=").last().unwrap().to_string());
+    }
+    if let Some(id) = id {
+        assert!(serialize::is_valid_id(&id), "invalid board id");
+        return serialize::load(&id);
+    }
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut specs = generator::generate_goal_specs(&GeneratorConfig::new(seed))
+        .expect("failed to generate a board");
+    assert!(goal < specs.len(), "the board has {} goals", specs.len());
+    let spec = specs.swap_remove(goal);
+    let state = play::random_state(&spec, seed);
+    (spec, state)
+}
+
+fn draw(
+    out: &mut impl Write,
+    session: &Session,
+    elapsed: Duration,
+    reveal: &Option<Reveal>,
+) -> io::Result<()> {
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    let goal_color = match session.spec.goals[0].target_type {
+        TargetType::Any => Color::Magenta,
+        TargetType::Particular(robot_index) => ROBOT_COLORS[robot_index],
+    };
+    for line in render::ascii(&session.spec, session.state(), None).lines() {
+        for cell in line.chars() {
+            match "RBGY".find(cell) {
+                Some(robot_index) => queue!(
+                    out,
+                    PrintStyledContent(cell.with(ROBOT_COLORS[robot_index]).bold())
+                )?,
+                None if cell == '*' => queue!(out, PrintStyledContent(cell.with(goal_color)))?,
+                None => queue!(out, Print(cell))?,
+            }
+        }
+        queue!(out, Print("\r\n"))?;
+    }
+
+    let selected = serialize::robot_index_to_color(session.selected as u8);
+    let status = match reveal {
+        Some(Reveal::Solving(_)) => "solving...".to_string(),
+        Some(Reveal::Animating(..)) => "showing the optimal solution".to_string(),
+        Some(Reveal::Done(None)) => "no solution".to_string(),
+        Some(Reveal::Done(Some(0))) => "already solved".to_string(),
+        Some(Reveal::Done(Some(moves))) => format!("optimal: {} moves", moves),
+        None if session.is_solved() => "solved!".to_string(),
+        None => String::new(),
+    };
+    queue!(
+        out,
+        Print(format!(
+            "\r\n{} robot | {} moves | {:02}:{:02} | {}\r\n",
+            selected,
+            session.moves().len(),
+            elapsed.as_secs() / 60,
+            elapsed.as_secs() % 60,
+            status
+        )),
+        Print("r/b/g/y select, arrows move, u undo, n redo, x reset, s reveal optimal, q quit\r\n")
+    )?;
+    out.flush()
+}
+
+fn main() -> io::Result<()> {
+    let (spec, state) = load();
+    let mut session = Session::new(spec, state);
+    let mut reveal: Option<Reveal> = None;
+    let started = Instant::now();
+    // the timer stops once the puzzle is solved
+    let mut finished: Option<Duration> = None;
+
+    let mut out = io::stdout();
+    let _terminal = RawTerminal::enter(&mut out)?;
+
+    loop {
+        let elapsed = finished.unwrap_or_else(|| started.elapsed());
+        draw(&mut out, &session, elapsed, &reveal)?;
+
+        match &mut reveal {
+            Some(Reveal::Solving(answer)) => match answer.try_recv() {
+                Ok(Some(moves)) => {
+                    session.reset();
+                    reveal = Some(Reveal::Animating(moves, Instant::now()));
+                }
+                Ok(None) => reveal = Some(Reveal::Done(None)),
+                Err(_) => {}
+            },
+            Some(Reveal::Animating(moves, shown)) if shown.elapsed() >= ANIMATION_STEP => {
+                if moves.is_empty() {
+                    reveal = Some(Reveal::Done(Some(session.moves().len())));
+                } else {
+                    session.push(moves.remove(0));
+                    *shown = Instant::now();
+                }
+            }
+            _ => {}
+        }
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        // keys only select robots and quit while the answer is being shown
+        let revealing = matches!(reveal, Some(Reveal::Solving(_) | Reveal::Animating(..)));
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            KeyCode::Char(key) if ROBOT_KEYS.contains(&key) => {
+                session.selected = ROBOT_KEYS.iter().position(|&k| k == key).unwrap();
+            }
+            _ if revealing => {}
+            KeyCode::Up => {
+                session.play(Direction::Up);
+            }
+            KeyCode::Down => {
+                session.play(Direction::Down);
+            }
+            KeyCode::Left => {
+                session.play(Direction::Left);
+            }
+            KeyCode::Right => {
+                session.play(Direction::Right);
+            }
+            KeyCode::Char('u') => {
+                session.undo();
+            }
+            KeyCode::Char('n') => {
+                session.redo();
+            }
+            KeyCode::Char('x') => session.reset(),
+            KeyCode::Char('s') => {
+                let spec = GameSpec::new(session.spec.walls, session.spec.goals.clone());
+                let state = session.initial_state.clone();
+                let (send, answer) = mpsc::channel();
+                thread::spawn(move || {
+                    let _ = send.send(solver::solve_bfs(&spec, &state).ok());
+                });
+                reveal = Some(Reveal::Solving(answer));
+                finished.get_or_insert(elapsed);
+            }
+            _ => {}
+        }
+        if session.is_solved() && reveal.is_none() {
+            finished.get_or_insert(elapsed);
+        }
+    }
+    Ok(())
+}
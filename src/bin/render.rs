@@ -0,0 +1,15 @@
+use std::{env, io, process};
+
+use ricochet_robots::cli;
+
+// `ricochet render` on the lines of stdin; `--svg` is `--format=svg`.
+fn main() {
+    let args: Vec<String> = ["render".to_string()]
+        .into_iter()
+        .chain(env::args().skip(1).map(|arg| match arg.as_str() {
+            "--svg" => "--format=svg".to_string(),
+            _ => arg,
+        }))
+        .collect();
+    process::exit(cli::run(&args, &mut io::stdout().lock()));
+}
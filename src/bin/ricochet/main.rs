@@ -0,0 +1,8 @@
+use std::{env, io, process};
+
+use ricochet_robots::cli;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    process::exit(cli::run(&args, &mut io::stdout().lock()));
+}
@@ -0,0 +1,41 @@
+use std::env;
+
+use ricochet_robots::room::{RoomConfig, RoomServer};
+use ricochet_robots::serialize;
+
+fn main() {
+    let (flags, args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let board = args.first().expect("usage: rooms <board id> [flags]");
+    let board = board.split("id=").last().unwrap();
+    assert!(serialize::is_valid_id(board), "invalid board id");
+
+    let mut port = 9001;
+    let mut config = RoomConfig::new(board);
+    for flag in flags {
+        let (name, value) = flag.split_once('=').unwrap_or((&flag, ""));
+        match name {
+            "--port" => port = value.parse().expect("port must be a number"),
+            "--seed" => config.seed = Some(value.parse().expect("seed must be a u64")),
+            // seconds of bidding after the first bid
+            "--timer" => {
+                config.rules.timer_seconds = value.parse().expect("timer must be a number")
+            }
+            // seconds without a bid until the round is lost, 0 to wait forever
+            "--idle" => {
+                let idle: u64 = value.parse().expect("idle must be a number");
+                config.rules.idle_seconds = (idle > 0).then_some(idle);
+            }
+            // seconds each demonstrator has, 0 to wait forever
+            "--demonstration" => {
+                let seconds: u64 = value.parse().expect("demonstration must be a number");
+                config.rules.demonstration_seconds = (seconds > 0).then_some(seconds);
+            }
+            _ => panic!("unknown flag {}", flag),
+        }
+    }
+
+    let server = RoomServer::start(("127.0.0.1", port), config).expect("failed to bind");
+    eprintln!("game rooms on ws://{}/", server.addr());
+    server.wait();
+}
@@ -0,0 +1,25 @@
+use std::env;
+use std::time::Duration;
+
+use ricochet_robots::service::{Service, ServiceConfig};
+
+fn main() {
+    let mut port = 8080;
+    let mut config = ServiceConfig::default();
+    for arg in env::args().skip(1) {
+        let (name, value) = arg.split_once('=').unwrap_or((&arg, ""));
+        let number = || value.parse::<usize>().expect("flag values must be numbers");
+        match name {
+            "--port" => port = value.parse().expect("--port must be at most 65535"),
+            "--workers" => config.workers = number(),
+            "--queue" => config.queue_size = number(),
+            // seconds per request, queueing included
+            "--timeout" => config.timeout = Duration::from_secs(number() as u64),
+            _ => panic!("unknown flag {}", arg),
+        }
+    }
+
+    let service = Service::start(("127.0.0.1", port), config).expect("failed to bind");
+    eprintln!("listening on http://{}/solve", service.addr());
+    service.wait();
+}
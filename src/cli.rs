@@ -0,0 +1,423 @@
+// the subcommands of the ricochet binary. the older single-purpose binaries
+// call into them too.
+
+mod commands;
+
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::{
+    json,
+    model::{GameSpec, GameState},
+    serialize,
+};
+
+pub use commands::{analyze, bench, convert, generate, render, solve, verify};
+
+// exit codes; with several inputs the highest one wins.
+pub const EXIT_UNSOLVED: i32 = 1;
+pub const EXIT_INVALID: i32 = 2;
+pub const EXIT_TIMED_OUT: i32 = 3;
+
+pub const USAGE: &str = "usage: ricochet <command> [flags] [puzzles...]
+
+puzzles are kaseken ids, replay urls or json puzzles, one per argument or per stdin line.
+
+commands:
+  solve     --input=auto|id|json --output=text|json|jsonl --algorithm=bfs|indexed --timeout=SECONDS
+            --explain --urls --all-targets, and --jobs=N --memory=MB for jsonl
+  generate  --seed=N --moves=4-6 --output=text|id|url|json and the generator flags of the generate binary
+  render    --input=... --format=ascii|svg --heatmap=red --moving-robots=N
+  verify    --input=... --moves=RU,BL,... --optimal --algorithm=... --timeout=SECONDS --output=text|json
+  convert   --input=... --to=id|url|json
+  analyze   --input=... --seed=N --goal=K --output=text|json, and the board rule flags of generate with --seed
+  bench     --input=... --algorithm=bfs|indexed --runs=N --output=text|json
+
+exit codes: 0 ok, 1 unsolvable or not verified, 2 invalid flags or input, 3 timed out";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    // json if the line starts with `{`, else a kaseken id or url
+    Auto,
+    Id,
+    Json,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<InputFormat, String> {
+        match text {
+            "auto" => Ok(InputFormat::Auto),
+            "id" => Ok(InputFormat::Id),
+            "json" => Ok(InputFormat::Json),
+            _ => Err(format!("unknown input format {}", text)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<OutputFormat, String> {
+        match text {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown output format {}", text)),
+        }
+    }
+}
+
+// "--name=value" or "--name"
+fn split_flag(flag: &str) -> (&str, &str) {
+    flag.split_once('=').unwrap_or((flag, ""))
+}
+
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value for {}: {:?}", name, value))
+}
+
+// the puzzles given as arguments, or else the non-empty lines of stdin.
+fn inputs(puzzles: &[String]) -> Vec<String> {
+    if !puzzles.is_empty() {
+        return puzzles.to_vec();
+    }
+    io::stdin()
+        .lock()
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .collect()
+}
+
+fn is_json(line: &str, format: InputFormat) -> bool {
+    match format {
+        InputFormat::Auto => line.trim_start().starts_with('{'),
+        InputFormat::Id => false,
+        InputFormat::Json => true,
+    }
+}
+
+// the kaseken id in a line holding an id or a replay url.
+fn board_id(line: &str) -> &str {
+    line.trim().split("id=").last().unwrap()
+}
+
+fn load(line: &str, format: InputFormat) -> Result<(GameSpec, GameState), String> {
+    if is_json(line, format) {
+        let puzzle: json::Puzzle = serde_json::from_str(line)
+            .map_err(|error| format!("invalid json puzzle: {}", error))?;
+        return puzzle.to_game();
+    }
+    let id = board_id(line);
+    if !serialize::is_valid_id(id) {
+        return Err(format!("invalid board id: {}", id));
+    }
+    Ok(serialize::load(id))
+}
+
+// runs `ricochet <args>`, printing to `out`, and returns the exit code.
+pub fn run(args: &[String], out: &mut impl Write) -> i32 {
+    let command = args.first().map_or("", String::as_str);
+    let (flags, puzzles): (Vec<String>, Vec<String>) = args
+        .iter()
+        .skip(1)
+        .cloned()
+        .partition(|arg| arg.starts_with("--"));
+    let result = match command {
+        "solve" => solve(&flags, &puzzles, out),
+        "generate" => generate(&flags, out),
+        "render" => render(&flags, &puzzles, out),
+        "verify" => verify(&flags, &puzzles, out),
+        "convert" => convert(&flags, &puzzles, out),
+        "analyze" => analyze(&flags, &puzzles, out),
+        "bench" => bench(&flags, &puzzles, out),
+        "help" | "--help" | "-h" => {
+            writeln!(out, "{}", USAGE).unwrap();
+            Ok(0)
+        }
+        _ => Err(format!("unknown command {:?}", command)),
+    };
+    result.unwrap_or_else(|message| {
+        eprintln!("{}\n\n{}", message, USAGE);
+        EXIT_INVALID
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{near_goal_id, small_spec, ID};
+    use crate::json::{SolveReport, SolveStatus};
+    use crate::model::{Direction, Goal, Point, TargetType};
+    use crate::solver;
+
+    // the exit code and output of `ricochet <args>`.
+    fn ricochet(args: &[&str]) -> (i32, String) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut out = Vec::new();
+        let code = run(&args, &mut out);
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    // the puzzle of `ID` one move away from its goal.
+    fn near_goal() -> String {
+        near_goal_id(&serialize::load(ID).0.goals[0])
+    }
+
+    // the puzzle of `ID` with its goal in the walled-in center.
+    fn unsolvable_id() -> String {
+        let (spec, state) = serialize::load(ID);
+        let goal = Goal {
+            position: Point::new(7, 8),
+            target_type: TargetType::Particular(0),
+        };
+        serialize::dump(&GameSpec::new(spec.walls, vec![goal]), &state)
+    }
+
+    // the --moves flag for the optimal solution of `id`.
+    fn moves_flag(id: &str) -> String {
+        let (spec, state) = serialize::load(id);
+        let moves: Vec<String> = solver::solve_bfs(&spec, &state)
+            .unwrap()
+            .iter()
+            .map(|game_move| {
+                let direction = match game_move.direction {
+                    Direction::Up => 'U',
+                    Direction::Down => 'D',
+                    Direction::Left => 'L',
+                    Direction::Right => 'R',
+                };
+                let robot = "RBGY".as_bytes()[game_move.robot_index as usize] as char;
+                format!("{}{}", robot, direction)
+            })
+            .collect();
+        format!("--moves={}", moves.join(","))
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        assert_eq!(ricochet(&["solvee"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&[]).0, EXIT_INVALID);
+        let (code, out) = ricochet(&["help"]);
+        assert_eq!((code, out.trim()), (0, USAGE));
+    }
+
+    #[test]
+    fn test_solve() {
+        let id = near_goal();
+        let (code, out) = ricochet(&["solve", "--urls", &id]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(code, 0);
+        assert_eq!(lines[0], "found a solution with 1 moves");
+        assert!(lines[1].starts_with("> Move "));
+        assert!(lines[2].trim().starts_with("https://"));
+
+        let (code, out) = ricochet(&["solve", "--output=json", &id]);
+        let result: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!((code, result["move_count"].as_u64()), (0, Some(1)));
+
+        let borderless = format!("vK{}", &id[2..]);
+        assert_eq!(ricochet(&["solve", "garbage"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["solve", &borderless]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["solve", &unsolvable_id()]).0, EXIT_UNSOLVED);
+        let timed_out = ["solve", "--algorithm=indexed", "--timeout=0", ID];
+        assert_eq!(ricochet(&timed_out).0, EXIT_TIMED_OUT);
+        // the highest code of all inputs wins
+        assert_eq!(ricochet(&["solve", &id, "garbage"]).0, EXIT_INVALID);
+
+        assert_eq!(ricochet(&["solve", "--timeout=1", &id]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["solve", "--bogus", &id]).0, EXIT_INVALID);
+    }
+
+    #[test]
+    fn test_solve_all_targets() {
+        let (code, out) = ricochet(&["solve", "--all-targets", &unsolvable_id()]);
+        assert_eq!(code, EXIT_UNSOLVED);
+        assert!(out.contains("unsolvable"), "{}", out);
+        let timed_out = ["solve", "--all-targets", "--timeout=0", ID];
+        assert_eq!(ricochet(&timed_out).0, EXIT_TIMED_OUT);
+        let json = ["solve", "--all-targets", "--output=json", ID];
+        assert_eq!(ricochet(&json).0, EXIT_INVALID);
+    }
+
+    #[test]
+    fn test_solve_jsonl() {
+        // an open top wall at (0, 0) must not take the batch down
+        let id = near_goal();
+        let borderless = format!("vK{}", &id[2..]);
+        let (code, out) = ricochet(&["solve", "--output=jsonl", &id, &borderless, "garbage"]);
+        assert_eq!(code, EXIT_INVALID);
+
+        let reports: Vec<SolveReport> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let statuses: Vec<SolveStatus> = reports.iter().map(|report| report.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                SolveStatus::Solved,
+                SolveStatus::ParseError,
+                SolveStatus::ParseError
+            ]
+        );
+        assert_eq!(reports[0].move_count, Some(1));
+
+        // explanations and urls are text only
+        for flag in ["--explain", "--urls"] {
+            for output in ["--output=jsonl", "--output=json"] {
+                assert_eq!(ricochet(&["solve", output, flag, &id]).0, EXIT_INVALID);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        assert_eq!(ricochet(&["generate", "--output=xml"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["generate", "--moves=9-4"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["generate", "--seed=x"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["generate", "--bogus"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["generate", "--goals=0"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["generate", "--symmetric"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["generate", "--center=17"]).0, EXIT_INVALID);
+    }
+
+    #[test]
+    fn test_render() {
+        let id = near_goal();
+        let (code, out) = ricochet(&["render", &id]);
+        assert_eq!(code, 0);
+        assert_eq!(
+            out,
+            crate::render::ascii(&serialize::load(&id).0, &serialize::load(&id).1, None)
+        );
+
+        let (code, out) = ricochet(&["render", "--format=svg", "--heatmap=red", &id]);
+        assert_eq!(code, 0);
+        assert!(out.starts_with("<svg"));
+
+        assert_eq!(ricochet(&["render", "garbage"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["render", "--format=png", &id]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["render", "--heatmap=pink", &id]).0, EXIT_INVALID);
+    }
+
+    #[test]
+    fn test_verify() {
+        let id = near_goal();
+        let moves = moves_flag(&id);
+        let (code, out) = ricochet(&["verify", &moves, "--optimal", &id]);
+        assert_eq!(
+            (code, out.trim()),
+            (0, "solved in 1 moves, which is optimal")
+        );
+
+        let (code, out) = ricochet(&["verify", "--moves=", &id]);
+        assert_eq!(
+            (code, out.trim()),
+            (EXIT_UNSOLVED, "the moves do not reach the goal")
+        );
+
+        // a solver that gives up verifies nothing
+        let args = [
+            "verify",
+            &moves,
+            "--optimal",
+            "--algorithm=indexed",
+            "--timeout=0",
+            "--output=json",
+            &id,
+        ];
+        let (code, out) = ricochet(&args);
+        let result: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(code, EXIT_TIMED_OUT);
+        assert_eq!(result["verified"], false);
+
+        assert_eq!(ricochet(&["verify", &id]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["verify", "--moves=XU", &id]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["verify", &moves, "garbage"]).0, EXIT_INVALID);
+    }
+
+    #[test]
+    fn test_convert() {
+        let (code, out) = ricochet(&["convert", "--to=url", ID]);
+        assert_eq!((code, out.trim()), (0, serialize::to_url(ID).as_str()));
+
+        let (code, json) = ricochet(&["convert", "--to=json", ID]);
+        assert_eq!(code, 0);
+        let (code, out) = ricochet(&["convert", "--to=id", json.trim()]);
+        let (spec, state) = serialize::load(ID);
+        assert_eq!(
+            (code, out.trim()),
+            (0, serialize::dump(&spec, &state).as_str())
+        );
+
+        assert_eq!(ricochet(&["convert", "garbage"]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["convert", "--to=svg", ID]).0, EXIT_INVALID);
+    }
+
+    #[test]
+    fn test_analyze() {
+        let goal = Goal {
+            position: Point::new(0, 0),
+            target_type: TargetType::Particular(0),
+        };
+        let (_, state) = serialize::load(ID);
+        let id = serialize::dump(&small_spec(goal), &state);
+        let (code, out) = ricochet(&["analyze", "--output=json", &id]);
+        let result: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(code, 0);
+        let solvable: u64 = result["depths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|count| count.as_u64().unwrap())
+            .sum();
+        // four robots on nine cells
+        assert_eq!(
+            solvable + result["unsolvable"].as_u64().unwrap(),
+            9 * 8 * 7 * 6
+        );
+
+        assert_eq!(ricochet(&["analyze", "garbage"]).0, EXIT_INVALID);
+        assert_eq!(
+            ricochet(&["analyze", "--seed=1", "--goal=99"]).0,
+            EXIT_INVALID
+        );
+        // the board rules shape the board of the seed
+        let flags = ["--seed=1", "--goals=2", "--goal=2"].map(String::from);
+        assert_eq!(
+            analyze(&flags, &[], &mut Vec::new()),
+            Err("the board has 2 goals".to_string())
+        );
+        assert_eq!(ricochet(&["analyze", "--goals=2", &id]).0, EXIT_INVALID);
+        assert_eq!(
+            ricochet(&["analyze", "--seed=1", "--moves=3"]).0,
+            EXIT_INVALID
+        );
+    }
+
+    #[test]
+    fn test_bench() {
+        let id = near_goal();
+        let (code, out) = ricochet(&["bench", "--runs=2", "--output=json", &id]);
+        let result: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(code, 0);
+        assert_eq!(
+            (result["move_count"].as_u64(), result["runs"].as_u64()),
+            (Some(1), Some(2))
+        );
+
+        assert_eq!(ricochet(&["bench", &unsolvable_id()]).0, EXIT_UNSOLVED);
+        assert_eq!(ricochet(&["bench", "--runs=0", &id]).0, EXIT_INVALID);
+        assert_eq!(ricochet(&["bench", "garbage"]).0, EXIT_INVALID);
+    }
+}
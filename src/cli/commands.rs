@@ -0,0 +1,752 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use rand::{self, Rng};
+use serde_json::json;
+use web_time::{Duration, Instant};
+
+use super::{
+    board_id, inputs, is_json, load, parse_value, split_flag, InputFormat, OutputFormat,
+    EXIT_INVALID, EXIT_TIMED_OUT, EXIT_UNSOLVED,
+};
+use crate::{
+    analysis::{self, Census},
+    batch::{BatchConfig, BatchSolver},
+    explain,
+    generator::{self, GenerateError, GeneratorConfig},
+    json::{Puzzle, SolveReport, Target},
+    model::{Direction, GameMove, GameSpec, GameState, Goal, TargetType},
+    render, serialize,
+    solver::{self, SolveError, SolveStats, Solver},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    // `solver::solve_bfs`
+    Bfs,
+    // `solver::Solver`: parent indices, buffers reused across puzzles, optional timeout
+    Indexed,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Algorithm, String> {
+        match text {
+            "bfs" => Ok(Algorithm::Bfs),
+            "indexed" => Ok(Algorithm::Indexed),
+            _ => Err(format!("unknown algorithm {}", text)),
+        }
+    }
+}
+
+struct Solving {
+    algorithm: Algorithm,
+    timeout: Option<Duration>,
+    // allocated on the first indexed solve
+    solver: Option<Solver>,
+}
+
+impl Solving {
+    fn new(algorithm: Algorithm, timeout: Option<Duration>) -> Result<Solving, String> {
+        if timeout.is_some() && algorithm == Algorithm::Bfs {
+            return Err("--timeout needs --algorithm=indexed".to_string());
+        }
+        Ok(Solving {
+            algorithm,
+            timeout,
+            solver: None,
+        })
+    }
+
+    // the moves, and the search stats when the algorithm keeps them.
+    fn solve(
+        &mut self,
+        spec: &GameSpec,
+        state: &GameState,
+    ) -> Result<(Vec<GameMove>, Option<SolveStats>), SolveError> {
+        match self.algorithm {
+            Algorithm::Bfs => Ok((solver::solve_bfs(spec, state)?, None)),
+            Algorithm::Indexed => {
+                let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+                let solution = self
+                    .solver
+                    .get_or_insert_with(Solver::new)
+                    .solve(spec, state, deadline)?;
+                Ok((solution.moves, Some(solution.stats)))
+            }
+        }
+    }
+}
+
+fn failure_code(error: &SolveError) -> i32 {
+    match error {
+        SolveError::TimedOut(_) => EXIT_TIMED_OUT,
+        _ => EXIT_UNSOLVED,
+    }
+}
+
+fn robot_index(color: &str) -> Result<usize, String> {
+    match color {
+        "red" => Ok(0),
+        "blue" => Ok(1),
+        "green" => Ok(2),
+        "yellow" => Ok(3),
+        _ => Err(format!("unknown robot {}", color)),
+    }
+}
+
+// "RU,BL,GD": robot letter then direction letter
+fn parse_moves(text: &str) -> Result<Vec<GameMove>, String> {
+    text.split(',')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let invalid = || format!("moves look like RU,BL,GD, not {}", token);
+            let mut letters = token.chars().map(|letter| letter.to_ascii_uppercase());
+            let robot_index = match letters.next() {
+                Some(letter) => "RBGY".find(letter).ok_or_else(invalid)?,
+                None => return Err(invalid()),
+            };
+            let direction = match (letters.next(), letters.next()) {
+                (Some('U'), None) => Direction::Up,
+                (Some('D'), None) => Direction::Down,
+                (Some('L'), None) => Direction::Left,
+                (Some('R'), None) => Direction::Right,
+                _ => return Err(invalid()),
+            };
+            Ok(GameMove {
+                robot_index: robot_index as u8,
+                direction,
+            })
+        })
+        .collect()
+}
+
+fn print_moves(moves: &[GameMove], out: &mut impl Write) {
+    for game_move in moves {
+        writeln!(
+            out,
+            "> Move {} to {:?} ",
+            serialize::robot_index_to_color(game_move.robot_index),
+            game_move.direction
+        )
+        .unwrap();
+    }
+}
+
+// the solution of every target chip of the board, or of the puzzle's own goals.
+fn print_all_targets(
+    board: Option<&str>,
+    spec: &GameSpec,
+    state: &GameState,
+    timeout: Option<Duration>,
+    out: &mut impl Write,
+) -> i32 {
+    let targets = board.map_or_else(|| spec.goals.clone(), serialize::load_board_goals);
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let solutions = solver::solve_all_targets(spec, state, &targets, None, deadline);
+    let mut code = 0;
+    for (target, solution) in targets.iter().zip(solutions) {
+        let color = match target.target_type {
+            TargetType::Any => "Any",
+            TargetType::Particular(robot_index) => {
+                serialize::robot_index_to_color(robot_index as u8)
+            }
+        };
+        let (r, c) = (target.position.r, target.position.c);
+        match solution {
+            Ok(solution) => {
+                writeln!(
+                    out,
+                    "{} target at ({}, {}): {} moves",
+                    color,
+                    r,
+                    c,
+                    solution.len()
+                )
+                .unwrap();
+                print_moves(&solution, out);
+            }
+            Err(error) => {
+                code = code.max(failure_code(&error));
+                writeln!(out, "{} target at ({}, {}): {}", color, r, c, error).unwrap();
+            }
+        }
+    }
+    code
+}
+
+// one json report per input line, in input order: finished lines wait until
+// the ones before them are printed.
+fn print_jsonl(
+    lines: &[String],
+    input: InputFormat,
+    config: BatchConfig,
+    out: &mut impl Write,
+) -> Result<i32, String> {
+    let mut batch = BatchSolver::new(config)?;
+    let mut code = 0;
+    let mut ids = Vec::new();
+    let mut reports: Vec<Option<SolveReport>> = Vec::new();
+    // the line of each puzzle
+    let mut line_indices = Vec::new();
+    let mut puzzles = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let loaded = load(line, input);
+        let id = match (&loaded, is_json(line, input)) {
+            (Ok((spec, state)), true) => serialize::dump(spec, state),
+            (Err(_), true) => line.trim().to_string(),
+            (_, false) => board_id(line).to_string(),
+        };
+        match loaded {
+            Ok(puzzle) => {
+                line_indices.push(index);
+                puzzles.push(puzzle);
+                reports.push(None);
+            }
+            Err(error) => {
+                code = EXIT_INVALID;
+                reports.push(Some(SolveReport::parse_error(&id, error)));
+            }
+        }
+        ids.push(id);
+    }
+
+    let mut printed = 0;
+    let mut print_ready = |reports: &[Option<SolveReport>]| {
+        while let Some(Some(report)) = reports.get(printed) {
+            writeln!(out, "{}", serde_json::to_string(report).unwrap()).unwrap();
+            printed += 1;
+        }
+    };
+    print_ready(&reports);
+    batch.solve_each(&puzzles, |index, result| {
+        let line = line_indices[index];
+        if let Err(error) = &result {
+            code = code.max(failure_code(error));
+        }
+        reports[line] = Some(SolveReport::new(&ids[line], &result));
+        print_ready(&reports);
+    });
+    print_ready(&reports);
+    Ok(code)
+}
+
+pub fn solve(flags: &[String], puzzles: &[String], out: &mut impl Write) -> Result<i32, String> {
+    let mut input = InputFormat::Auto;
+    let mut output = OutputFormat::Text;
+    let mut jsonl = false;
+    let mut algorithm = Algorithm::Bfs;
+    let mut timeout = None;
+    let mut print_explanations = false;
+    let mut print_urls = false;
+    let mut all_targets = false;
+    let mut batch = BatchConfig {
+        threads: 1,
+        ..BatchConfig::default()
+    };
+    for flag in flags {
+        let (name, value) = split_flag(flag);
+        match name {
+            "--input" => input = value.parse()?,
+            "--output" if value == "jsonl" => jsonl = true,
+            "--output" => output = value.parse()?,
+            "--algorithm" => algorithm = value.parse()?,
+            "--timeout" => timeout = Some(Duration::from_secs(parse_value(name, value)?)),
+            "--explain" => print_explanations = true,
+            "--urls" => print_urls = true,
+            "--all-targets" => all_targets = true,
+            "--jobs" => batch.threads = parse_value(name, value)?,
+            "--memory" => batch.memory_limit = parse_value::<usize>(name, value)? << 20,
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    if all_targets && (jsonl || output != OutputFormat::Text) {
+        return Err("--all-targets only has text output".to_string());
+    }
+    if (print_explanations || print_urls) && (jsonl || output != OutputFormat::Text) {
+        return Err("--explain and --urls only have text output".to_string());
+    }
+    if jsonl {
+        batch.timeout = timeout;
+        return print_jsonl(&inputs(puzzles), input, batch, out);
+    }
+    // --all-targets runs a search of its own, which takes the timeout itself
+    let mut solving = Solving::new(algorithm, timeout.filter(|_| !all_targets))?;
+
+    let mut code = 0;
+    for line in inputs(puzzles) {
+        let (spec, state) = match load(&line, input) {
+            Ok(game) => game,
+            Err(error) => {
+                eprintln!("{}", error);
+                code = code.max(EXIT_INVALID);
+                continue;
+            }
+        };
+        // the id of the whole board, with all of its target chips
+        let board = (!is_json(&line, input)).then(|| board_id(&line));
+        if all_targets {
+            code = code.max(print_all_targets(board, &spec, &state, timeout, out));
+            continue;
+        }
+        let (moves, stats) = match solving.solve(&spec, &state) {
+            Ok(solution) => solution,
+            Err(error) => {
+                code = code.max(failure_code(&error));
+                match output {
+                    OutputFormat::Text => writeln!(out, "{}", error),
+                    OutputFormat::Json => {
+                        writeln!(out, "{}", json!({ "error": error.to_string() }))
+                    }
+                }
+                .unwrap();
+                continue;
+            }
+        };
+
+        if output == OutputFormat::Json {
+            let mut result = json!({ "moves": moves, "move_count": moves.len() });
+            if let Some(stats) = stats {
+                result["explored_states"] = json!(stats.explored_states);
+                result["solve_ms"] = json!(stats.elapsed.as_millis() as u64);
+            }
+            writeln!(out, "{}", result).unwrap();
+            continue;
+        }
+        writeln!(out, "found a solution with {} moves", moves.len()).unwrap();
+        let replay_ids = serialize::replay_ids(&spec, &state, &moves);
+        // the solver only returns moves of the board's robots
+        let explanations = explain::explain(&spec, &state, &moves).unwrap();
+        for ((game_move, id), explanation) in moves
+            .iter()
+            .zip(replay_ids.iter().skip(1))
+            .zip(&explanations)
+        {
+            print_moves(std::slice::from_ref(game_move), out);
+            if print_explanations {
+                writeln!(out, "  {}", explanation).unwrap();
+            }
+            if print_urls {
+                // keep every target chip of the board when the input was an id
+                let id = board.map_or_else(|| id.clone(), |board| serialize::unify_ids(board, id));
+                writeln!(out, "  {}", serialize::to_url(&id)).unwrap();
+            }
+        }
+    }
+    Ok(code)
+}
+
+pub fn generate(flags: &[String], out: &mut impl Write) -> Result<i32, String> {
+    let mut seed = None;
+    let mut output = "text";
+    let mut config = GeneratorConfig::new(0);
+    for flag in flags {
+        let (name, value) = split_flag(flag);
+        match name {
+            "--seed" => seed = Some(parse_value(name, value)?),
+            "--output" if ["text", "id", "url", "json"].contains(&value) => output = value,
+            "--output" => return Err(format!("unknown output format {}", value)),
+            _ if config.apply_flag(name, value)? => {}
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    config.seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+
+    let puzzle = match generator::generate(&config) {
+        Ok(puzzle) => puzzle,
+        Err(GenerateError::InvalidConfig(message)) => return Err(message),
+        Err(GenerateError::NoPuzzle(failure)) => {
+            eprintln!("{} (seed {})", failure, config.seed);
+            return Ok(EXIT_UNSOLVED);
+        }
+    };
+    let id = serialize::dump(&puzzle.spec, &puzzle.state);
+    match output {
+        "id" => writeln!(out, "{}", id),
+        "url" => writeln!(out, "{}", serialize::to_url(&id)),
+        "json" => writeln!(
+            out,
+            "{}",
+            json!({
+                "seed": puzzle.seed,
+                "id": id,
+                "moves": puzzle.moves,
+                "solutions": puzzle.solutions,
+                "puzzle": Puzzle::from_game(&puzzle.spec, &puzzle.state),
+            })
+        ),
+        _ => {
+            writeln!(out, "seed {}: {} optimal moves", puzzle.seed, puzzle.moves).unwrap();
+            if let Some(solutions) = puzzle.solutions {
+                writeln!(out, "optimal solutions: {}", solutions).unwrap();
+            }
+            writeln!(out, "{}", serialize::to_url(&id))
+        }
+    }
+    .unwrap();
+    Ok(0)
+}
+
+pub fn render(flags: &[String], puzzles: &[String], out: &mut impl Write) -> Result<i32, String> {
+    let mut input = InputFormat::Auto;
+    let mut svg = false;
+    let mut heatmap = None;
+    let mut moving_robots = None;
+    for flag in flags {
+        let (name, value) = split_flag(flag);
+        match name {
+            "--input" => input = value.parse()?,
+            "--format" if value == "ascii" => svg = false,
+            "--format" if value == "svg" => svg = true,
+            "--format" => return Err(format!("unknown render format {}", value)),
+            "--heatmap" => heatmap = Some(robot_index(value)?),
+            "--moving-robots" => moving_robots = Some(parse_value(name, value)?),
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+
+    let mut code = 0;
+    for line in inputs(puzzles) {
+        let (spec, state) = match load(&line, input) {
+            Ok(game) => game,
+            Err(error) => {
+                eprintln!("{}", error);
+                code = code.max(EXIT_INVALID);
+                continue;
+            }
+        };
+        let overlay = heatmap.map(|robot_index| match moving_robots {
+            Some(max_moves) => {
+                spec.robot_distances_with_moving_robots(&state, robot_index, max_moves)
+            }
+            None => spec.robot_distances(&state, robot_index),
+        });
+        if svg {
+            write!(out, "{}", render::svg(&spec, &state, overlay.as_ref())).unwrap();
+        } else {
+            write!(out, "{}", render::ascii(&spec, &state, overlay.as_ref())).unwrap();
+        }
+    }
+    Ok(code)
+}
+
+pub fn verify(flags: &[String], puzzles: &[String], out: &mut impl Write) -> Result<i32, String> {
+    let mut input = InputFormat::Auto;
+    let mut output = OutputFormat::Text;
+    let mut moves = None;
+    let mut optimal = false;
+    let mut algorithm = Algorithm::Bfs;
+    let mut timeout = None;
+    for flag in flags {
+        let (name, value) = split_flag(flag);
+        match name {
+            "--input" => input = value.parse()?,
+            "--output" => output = value.parse()?,
+            "--moves" => moves = Some(parse_moves(value)?),
+            "--optimal" => optimal = true,
+            "--algorithm" => algorithm = value.parse()?,
+            "--timeout" => timeout = Some(Duration::from_secs(parse_value(name, value)?)),
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    let moves = moves.ok_or("verify needs --moves")?;
+    let mut solving = Solving::new(algorithm, timeout)?;
+
+    let mut code = 0;
+    for line in inputs(puzzles) {
+        let (spec, state) = match load(&line, input) {
+            Ok(game) => game,
+            Err(error) => {
+                eprintln!("{}", error);
+                code = code.max(EXIT_INVALID);
+                continue;
+            }
+        };
+        let final_state = moves.iter().fold(state.clone(), |state, game_move| {
+            spec.apply_move(&state, game_move)
+        });
+        let solved = spec.is_winning_state(&final_state);
+        let best = match solved && optimal {
+            true => match solving.solve(&spec, &state) {
+                Ok((best, _)) => Some(best.len()),
+                // without the optimal length the moves are not verified
+                Err(error) => {
+                    code = code.max(failure_code(&error));
+                    match output {
+                        OutputFormat::Text => writeln!(out, "{}", error),
+                        OutputFormat::Json => writeln!(
+                            out,
+                            "{}",
+                            json!({
+                                "solved": solved,
+                                "move_count": moves.len(),
+                                "verified": false,
+                                "error": error.to_string(),
+                            })
+                        ),
+                    }
+                    .unwrap();
+                    continue;
+                }
+            },
+            false => None,
+        };
+        let verified = solved && best.is_none_or(|best| best == moves.len());
+        if !verified {
+            code = code.max(EXIT_UNSOLVED);
+        }
+
+        match output {
+            OutputFormat::Json => writeln!(
+                out,
+                "{}",
+                json!({
+                    "solved": solved,
+                    "move_count": moves.len(),
+                    "optimal_move_count": best,
+                    "verified": verified,
+                })
+            ),
+            OutputFormat::Text if !solved => writeln!(out, "the moves do not reach the goal"),
+            OutputFormat::Text => match best {
+                Some(best) if best < moves.len() => writeln!(
+                    out,
+                    "solved in {} moves, but the optimal solution has {}",
+                    moves.len(),
+                    best
+                ),
+                Some(_) => writeln!(out, "solved in {} moves, which is optimal", moves.len()),
+                None => writeln!(out, "solved in {} moves", moves.len()),
+            },
+        }
+        .unwrap();
+    }
+    Ok(code)
+}
+
+pub fn convert(flags: &[String], puzzles: &[String], out: &mut impl Write) -> Result<i32, String> {
+    let mut input = InputFormat::Auto;
+    let mut to = "id";
+    for flag in flags {
+        let (name, value) = split_flag(flag);
+        match name {
+            "--input" => input = value.parse()?,
+            "--to" if ["id", "url", "json"].contains(&value) => to = value,
+            "--to" => return Err(format!("unknown output format {}", value)),
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+
+    let mut code = 0;
+    for line in inputs(puzzles) {
+        let (spec, state) = match load(&line, input) {
+            Ok(game) => game,
+            Err(error) => {
+                eprintln!("{}", error);
+                code = code.max(EXIT_INVALID);
+                continue;
+            }
+        };
+        // ids are passed through so the other target chips of the board survive
+        let id = match is_json(&line, input) {
+            false => board_id(&line).to_string(),
+            true => serialize::dump(&spec, &state),
+        };
+        match to {
+            "id" => writeln!(out, "{}", id),
+            "url" => writeln!(out, "{}", serialize::to_url(&id)),
+            _ => writeln!(
+                out,
+                "{}",
+                serde_json::to_string(&Puzzle::from_game(&spec, &state)).unwrap()
+            ),
+        }
+        .unwrap();
+    }
+    Ok(code)
+}
+
+fn print_census(
+    goal: &Goal,
+    census: &Census,
+    label: &str,
+    output: OutputFormat,
+    out: &mut impl Write,
+) {
+    match output {
+        OutputFormat::Json => writeln!(
+            out,
+            "{}",
+            json!({
+                "board": label,
+                "goal": Target::from(goal),
+                "depths": census.depths,
+                "unsolvable": census.unsolvable,
+                "mean": census.mean(),
+                "median": census.median(),
+            })
+        ),
+        OutputFormat::Text => writeln!(
+            out,
+            "{} goal at ({}, {})\n{}",
+            label, goal.position.r, goal.position.c, census
+        ),
+    }
+    .unwrap();
+}
+
+// the generator flags that shape the board rather than pick the puzzle on it.
+const BOARD_RULE_FLAGS: [&str; 7] = [
+    "--goals",
+    "--edge-walls",
+    "--center",
+    "--goal-spacing",
+    "--max-cell-walls",
+    "--symmetric",
+    "--corners",
+];
+
+pub fn analyze(flags: &[String], puzzles: &[String], out: &mut impl Write) -> Result<i32, String> {
+    let mut input = InputFormat::Auto;
+    let mut output = OutputFormat::Text;
+    let mut seed = None;
+    let mut goal = None;
+    // the board rules of generate, so that --seed gives the board generate made
+    let mut config = GeneratorConfig::new(0);
+    let mut board_rules = false;
+    for flag in flags {
+        let (name, value) = split_flag(flag);
+        match name {
+            "--input" => input = value.parse()?,
+            "--output" => output = value.parse()?,
+            "--seed" => seed = Some(parse_value::<u64>(name, value)?),
+            "--goal" => goal = Some(parse_value::<usize>(name, value)?),
+            _ if BOARD_RULE_FLAGS.contains(&name) && config.apply_flag(name, value)? => {
+                board_rules = true;
+            }
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    if board_rules && seed.is_none() {
+        return Err("board rule flags only apply to boards made with --seed".to_string());
+    }
+
+    if let Some(seed) = seed {
+        config.seed = seed;
+        let specs = generator::generate_goal_specs(&config)?;
+        if goal.is_some_and(|goal| goal >= specs.len()) {
+            return Err(format!("the board has {} goals", specs.len()));
+        }
+        for (index, spec) in specs.iter().enumerate() {
+            if goal.is_none_or(|goal| goal == index) {
+                let label = format!("seed {} goal #{}", seed, index);
+                let census = analysis::census(spec, &spec.goals[0]);
+                print_census(&spec.goals[0], &census, &label, output, out);
+            }
+        }
+        return Ok(0);
+    }
+
+    let mut code = 0;
+    for line in inputs(puzzles) {
+        let (spec, _) = match load(&line, input) {
+            Ok(game) => game,
+            Err(error) => {
+                eprintln!("{}", error);
+                code = code.max(EXIT_INVALID);
+                continue;
+            }
+        };
+        for goal in &spec.goals {
+            let census = analysis::census(&spec, goal);
+            print_census(goal, &census, line.trim(), output, out);
+        }
+    }
+    Ok(code)
+}
+
+pub fn bench(flags: &[String], puzzles: &[String], out: &mut impl Write) -> Result<i32, String> {
+    let mut input = InputFormat::Auto;
+    let mut output = OutputFormat::Text;
+    let mut algorithm = Algorithm::Bfs;
+    let mut runs: usize = 3;
+    for flag in flags {
+        let (name, value) = split_flag(flag);
+        match name {
+            "--input" => input = value.parse()?,
+            "--output" => output = value.parse()?,
+            "--algorithm" => algorithm = value.parse()?,
+            "--runs" => runs = parse_value(name, value)?,
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    if runs == 0 {
+        return Err("--runs must be at least 1".to_string());
+    }
+    let mut solving = Solving::new(algorithm, None)?;
+
+    let mut code = 0;
+    for line in inputs(puzzles) {
+        let (spec, state) = match load(&line, input) {
+            Ok(game) => game,
+            Err(error) => {
+                eprintln!("{}", error);
+                code = code.max(EXIT_INVALID);
+                continue;
+            }
+        };
+        let mut times = Vec::with_capacity(runs);
+        let mut result = None;
+        for _ in 0..runs {
+            let start = Instant::now();
+            result = Some(solving.solve(&spec, &state));
+            times.push(start.elapsed());
+        }
+        let (moves, stats) = match result.unwrap() {
+            Ok(solution) => solution,
+            Err(error) => {
+                eprintln!("{}", error);
+                code = code.max(failure_code(&error));
+                continue;
+            }
+        };
+
+        let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let min = ms(*times.iter().min().unwrap());
+        let max = ms(*times.iter().max().unwrap());
+        let mean = ms(times.iter().sum::<Duration>()) / runs as f64;
+        match output {
+            OutputFormat::Json => writeln!(
+                out,
+                "{}",
+                json!({
+                    "move_count": moves.len(),
+                    "runs": runs,
+                    "min_ms": min,
+                    "mean_ms": mean,
+                    "max_ms": max,
+                    "explored_states": stats.map(|stats| stats.explored_states),
+                })
+            ),
+            OutputFormat::Text => {
+                write!(
+                    out,
+                    "{} moves: min {:.1} ms, mean {:.1} ms, max {:.1} ms over {} runs",
+                    moves.len(),
+                    min,
+                    mean,
+                    max,
+                    runs
+                )
+                .unwrap();
+                match stats {
+                    Some(stats) => writeln!(out, ", {} states", stats.explored_states),
+                    None => writeln!(out),
+                }
+            }
+        }
+        .unwrap();
+    }
+    Ok(code)
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use crate::model::{Direction, GameMove, GameSpec, GameState, Point, BOARD_SIZE, ROBOT_COUNT};
+use crate::serialize::robot_index_to_color;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stopper {
+    // the wall on the `direction` side of `position`
+    Wall {
+        position: Point,
+        direction: Direction,
+    },
+    Robot(u8),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveExplanation {
+    pub game_move: GameMove,
+    pub from: Point,
+    pub to: Point,
+    pub distance: u8,
+    pub stopped_by: Stopper,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplainError {
+    // a move names a robot the board doesn't have
+    UnknownRobot(u8),
+    // the robot stops with neither a wall nor a robot in front of it, which only
+    // a state with robots on top of each other allows
+    NoStopper(u8),
+}
+
+impl fmt::Display for ExplainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExplainError::UnknownRobot(robot) => write!(f, "unknown robot {}", robot),
+            ExplainError::NoStopper(robot) => {
+                write!(f, "nothing stops robot {} where it stops", robot)
+            }
+        }
+    }
+}
+
+pub fn explain_move(
+    spec: &GameSpec,
+    state: &GameState,
+    game_move: &GameMove,
+) -> Result<MoveExplanation, ExplainError> {
+    let robot_index = game_move.robot_index as usize;
+    if robot_index >= ROBOT_COUNT {
+        return Err(ExplainError::UnknownRobot(game_move.robot_index));
+    }
+    let from = state.robots[robot_index];
+    let to = spec.apply_move(state, game_move).robots[robot_index];
+    let stopped_by = if spec.has_wall(to, game_move.direction) {
+        Stopper::Wall {
+            position: to,
+            direction: game_move.direction,
+        }
+    } else {
+        let blocker = to + Point::from(game_move.direction);
+        let blocker_index = state
+            .robots
+            .iter()
+            .position(|robot| *robot == blocker)
+            .ok_or(ExplainError::NoStopper(game_move.robot_index))?;
+        Stopper::Robot(blocker_index as u8)
+    };
+    Ok(MoveExplanation {
+        game_move: game_move.clone(),
+        from,
+        to,
+        distance: from.chebyshev(&to) as u8,
+        stopped_by,
+    })
+}
+
+pub fn explain(
+    spec: &GameSpec,
+    initial_state: &GameState,
+    moves: &[GameMove],
+) -> Result<Vec<MoveExplanation>, ExplainError> {
+    let mut state = initial_state.clone();
+    moves
+        .iter()
+        .map(|game_move| {
+            let explanation = explain_move(spec, &state, game_move)?;
+            state = spec.apply_move(&state, game_move);
+            Ok(explanation)
+        })
+        .collect()
+}
+
+fn is_edge(position: Point, direction: Direction) -> bool {
+    let next = position + Point::from(direction);
+    next.r < 0 || next.c < 0 || next.r >= BOARD_SIZE as i8 || next.c >= BOARD_SIZE as i8
+}
+
+impl fmt::Display for MoveExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} moves {:?} from ({}, {}) to ({}, {}), {} cells, stopped by ",
+            robot_index_to_color(self.game_move.robot_index),
+            self.game_move.direction,
+            self.from.r,
+            self.from.c,
+            self.to.r,
+            self.to.c,
+            self.distance
+        )?;
+        match &self.stopped_by {
+            Stopper::Wall {
+                position,
+                direction,
+            } if is_edge(*position, *direction) => {
+                write!(f, "the board edge")
+            }
+            Stopper::Wall {
+                position,
+                direction,
+            } => write!(
+                f,
+                "the wall on the {:?} side of ({}, {})",
+                direction, position.r, position.c
+            ),
+            Stopper::Robot(robot_index) => write!(f, "{}", robot_index_to_color(*robot_index)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{small_spec, ID};
+    use crate::model::{Goal, TargetType, GAME_MOVES};
+    use crate::serialize;
+
+    #[test]
+    fn test_explain_move() {
+        let (spec, state) = serialize::load(ID);
+        for game_move in GAME_MOVES.iter() {
+            let explanation = explain_move(&spec, &state, game_move).unwrap();
+            let robot_index = game_move.robot_index as usize;
+            assert_eq!(explanation.from, state.robots[robot_index]);
+            assert_eq!(
+                explanation.from + Point::from(game_move.direction) * explanation.distance as i8,
+                explanation.to
+            );
+            match explanation.stopped_by {
+                Stopper::Wall {
+                    position,
+                    direction,
+                } => {
+                    assert_eq!(position, explanation.to);
+                    assert!(spec.has_wall(position, direction));
+                }
+                Stopper::Robot(blocker) => assert_eq!(
+                    state.robots[blocker as usize],
+                    explanation.to + Point::from(game_move.direction)
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_explain_text() {
+        let (spec, state) = serialize::load(ID);
+        let moves = [
+            GameMove {
+                robot_index: 0,
+                direction: Direction::Up,
+            },
+            GameMove {
+                robot_index: 1,
+                direction: Direction::Left,
+            },
+        ];
+        let lines: Vec<String> = explain(&spec, &state, &moves)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "Red moves Up from (8, 14) to (3, 14), 5 cells, stopped by the wall on the Up side of (3, 14)",
+                "Blue moves Left from (14, 5) to (14, 0), 5 cells, stopped by the board edge",
+            ]
+        );
+
+        let goal = Goal {
+            position: Point::new(1, 1),
+            target_type: TargetType::Any,
+        };
+        let state = GameState {
+            robots: [
+                Point::new(0, 1),
+                Point::new(2, 1),
+                Point::new(1, 0),
+                Point::new(1, 2),
+            ],
+        };
+        let game_move = GameMove {
+            robot_index: 0,
+            direction: Direction::Down,
+        };
+        let explanation = explain_move(&small_spec(goal), &state, &game_move).unwrap();
+        assert_eq!(
+            explanation.to_string(),
+            "Red moves Down from (0, 1) to (1, 1), 1 cells, stopped by Blue"
+        );
+    }
+
+    #[test]
+    fn test_unknown_robot() {
+        let (spec, state) = serialize::load(ID);
+        let game_move = GameMove {
+            robot_index: 4,
+            direction: Direction::Up,
+        };
+        assert_eq!(
+            explain_move(&spec, &state, &game_move),
+            Err(ExplainError::UnknownRobot(4))
+        );
+        assert_eq!(ExplainError::UnknownRobot(4).to_string(), "unknown robot 4");
+    }
+}
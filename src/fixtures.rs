@@ -0,0 +1,46 @@
+// puzzles and helpers shared by the tests of several modules.
+
+use crate::model::{GameSpec, GameState, Goal, WALL_MAP_SIZE};
+use crate::serialize;
+
+// a board with all of its target chips.
+pub const ID: &str = "rKNKXKXIrKxj_-_------7Xm-Yv-_-_Zv------m-Yl-B---_n---j-L---_---Zr---_XQ---R----g1__--n---Z07-m-Zv----K--_-R---L--Zfm_j--RL---L_Yr-B------Ylj-L_------nX--Yun---ZeVeXKWKjKXDq96WCmVjIcx4YUUWPKC8tOexuLoEh";
+
+// a 7 move puzzle from input.txt
+pub const EASY_ID: &str = "rKHKNKXIrKM--7--_n---m-----__--Vv------Yv-N-B-_-----Ln-L-m--L-RJk---_XRL--R_-X-g1--j-n--NZ07-XXZf----K--N-BL---Vv----7----X----Zk-----Lj--R__---RLX--n-m------_leXKXAXKVeWDn94ytfICCphpWL97KIP4sVqKkqtUi";
+
+// `state` with the robot of `goal` on it, then stepped back `steps` times
+// without passing the goal.
+pub fn near_goal_state(spec: &GameSpec, state: &GameState, goal: &Goal, steps: usize) -> GameState {
+    let mut state = state.clone();
+    state.robots[goal.target_type.robot_index(0)] = goal.position;
+    for _ in 0..steps {
+        state = spec
+            .prev_states(&state)
+            .into_iter()
+            .rfind(|prev_state| !goal.is_reached(prev_state))
+            .unwrap();
+    }
+    state
+}
+
+// `ID` with its robots one move away from `goal`, keeping every target chip.
+pub fn near_goal_id(goal: &Goal) -> String {
+    let (spec, state) = serialize::load(ID);
+    let state = near_goal_state(&spec, &state, goal, 1);
+    serialize::unify_ids(ID, &serialize::dump(&spec, &state))
+}
+
+// a board whose only open cells are the 3x3 square in its top left corner.
+pub fn small_spec(goal: Goal) -> GameSpec {
+    let mut walls = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+    for (r, row) in walls.iter_mut().enumerate() {
+        for (c, wall) in row.iter_mut().enumerate() {
+            // wall segments sit between two cells
+            let between = r.is_multiple_of(2) != c.is_multiple_of(2);
+            let inside = (1..6).contains(&r) && (1..6).contains(&c);
+            *wall = between && !inside;
+        }
+    }
+    GameSpec::new(walls, vec![goal])
+}
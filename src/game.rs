@@ -0,0 +1,207 @@
+// a game session: the robots stay where the last round left them and
+// target chips are drawn one by one from the board's full set.
+
+use std::fmt;
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::model::{GameMove, GameSpec, GameState, Goal, ROBOT_COUNT};
+use crate::serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundError {
+    NoTarget,
+    UnknownPlayer(usize),
+    // a move names a robot the board doesn't have
+    UnknownRobot(u8),
+    // the moves don't bring the robot to the target
+    NotSolved,
+}
+
+impl fmt::Display for RoundError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoundError::NoTarget => write!(f, "no target chip has been drawn"),
+            RoundError::UnknownPlayer(player) => write!(f, "unknown player {}", player),
+            RoundError::UnknownRobot(robot) => write!(f, "unknown robot {}", robot),
+            RoundError::NotSolved => write!(f, "the moves do not reach the target"),
+        }
+    }
+}
+
+// the state after the moves, if it reaches the goal.
+pub fn verify(
+    spec: &GameSpec,
+    state: &GameState,
+    goal: &Goal,
+    moves: &[GameMove],
+) -> Result<GameState, RoundError> {
+    if let Some(game_move) = moves
+        .iter()
+        .find(|game_move| game_move.robot_index as usize >= ROBOT_COUNT)
+    {
+        return Err(RoundError::UnknownRobot(game_move.robot_index));
+    }
+    let final_state = moves.iter().fold(state.clone(), |state, game_move| {
+        spec.apply_move(&state, game_move)
+    });
+    goal.is_reached(&final_state)
+        .then_some(final_state)
+        .ok_or(RoundError::NotSolved)
+}
+
+#[derive(Debug)]
+pub struct Game {
+    // walls of the board, with every target chip as a goal.
+    pub spec: GameSpec,
+    pub state: GameState,
+    // indices into `spec.goals`, drawn from the back.
+    deck: Vec<usize>,
+    current: Option<usize>,
+    // chips won by each player
+    pub chips: Vec<Vec<usize>>,
+}
+
+impl Game {
+    // `seed` shuffles the deck; without it chips are drawn in board order.
+    pub fn new(spec: GameSpec, state: GameState, players: usize, seed: Option<u64>) -> Game {
+        let mut deck: Vec<usize> = (0..spec.goals.len()).rev().collect();
+        if let Some(seed) = seed {
+            deck.shuffle(&mut StdRng::seed_from_u64(seed));
+        }
+        Game {
+            spec,
+            state,
+            deck,
+            current: None,
+            chips: vec![Vec::new(); players],
+        }
+    }
+
+    pub fn from_id(id: &str, players: usize, seed: Option<u64>) -> Game {
+        let (spec, state) = serialize::load(id);
+        let spec = GameSpec::new(spec.walls, serialize::load_board_goals(id));
+        Game::new(spec, state, players, seed)
+    }
+
+    // index of the new player, who starts without chips.
+    pub fn add_player(&mut self) -> usize {
+        self.chips.push(Vec::new());
+        self.chips.len() - 1
+    }
+
+    pub fn remaining_chips(&self) -> usize {
+        self.deck.len()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.current.is_none() && self.deck.is_empty()
+    }
+
+    pub fn current_target(&self) -> Option<&Goal> {
+        self.current.map(|index| &self.spec.goals[index])
+    }
+
+    // spec with only the current target as its goal, for the solver.
+    pub fn round_spec(&self) -> Option<GameSpec> {
+        self.current_target()
+            .map(|goal| GameSpec::new(self.spec.walls, vec![goal.clone()]))
+    }
+
+    pub fn draw(&mut self) -> Option<&Goal> {
+        if self.current.is_none() {
+            self.current = self.deck.pop();
+        }
+        self.current_target()
+    }
+
+    // the player wins the chip and the robots stay where the moves leave them.
+    pub fn complete_round(&mut self, player: usize, moves: &[GameMove]) -> Result<(), RoundError> {
+        let goal = self.current_target().ok_or(RoundError::NoTarget)?;
+        if player >= self.chips.len() {
+            return Err(RoundError::UnknownPlayer(player));
+        }
+        let final_state = verify(&self.spec, &self.state, goal, moves)?;
+        self.state = final_state;
+        self.chips[player].push(self.current.take().unwrap());
+        Ok(())
+    }
+
+    // nobody solved it; the chip goes to the bottom of the deck.
+    pub fn pass_round(&mut self) -> Result<(), RoundError> {
+        let index = self.current.take().ok_or(RoundError::NoTarget)?;
+        self.deck.insert(0, index);
+        Ok(())
+    }
+
+    // takes the player's most recent chip back to the bottom of the deck.
+    pub fn return_chip(&mut self, player: usize) -> bool {
+        match self.chips.get_mut(player).and_then(|chips| chips.pop()) {
+            Some(index) => {
+                self.deck.insert(0, index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn scores(&self) -> Vec<usize> {
+        self.chips.iter().map(|chips| chips.len()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{near_goal_state, ID};
+    use crate::model::{Direction, TargetType};
+    use crate::solver;
+
+    #[test]
+    fn test_seeded_deck() {
+        let draw_all = |seed| {
+            let mut game = Game::from_id(ID, 1, seed);
+            let mut drawn = Vec::new();
+            while let Some(goal) = game.draw() {
+                drawn.push(goal.clone());
+                game.pass_round().unwrap();
+                if drawn.len() == game.spec.goals.len() {
+                    break;
+                }
+            }
+            drawn
+        };
+        assert_eq!(draw_all(Some(1)), draw_all(Some(1)));
+        assert_ne!(draw_all(Some(1)), draw_all(Some(2)));
+        assert_eq!(draw_all(None), serialize::load_board_goals(ID));
+    }
+
+    #[test]
+    fn test_round() {
+        let mut game = Game::from_id(ID, 2, None);
+        assert_eq!(game.complete_round(0, &[]), Err(RoundError::NoTarget));
+        let goal = game.draw().unwrap().clone();
+        assert_eq!(goal.target_type, TargetType::Particular(0));
+
+        game.state = near_goal_state(&game.spec, &game.state, &goal, 1);
+
+        assert_eq!(
+            game.complete_round(5, &[]),
+            Err(RoundError::UnknownPlayer(5))
+        );
+        assert_eq!(game.complete_round(1, &[]), Err(RoundError::NotSolved));
+        let unknown_robot = GameMove {
+            robot_index: 7,
+            direction: Direction::Up,
+        };
+        assert_eq!(
+            game.complete_round(1, &[unknown_robot]),
+            Err(RoundError::UnknownRobot(7))
+        );
+        let moves = solver::solve_bfs(&game.round_spec().unwrap(), &game.state).unwrap();
+        game.complete_round(1, &moves).unwrap();
+        assert!(goal.is_reached(&game.state));
+        assert_eq!(game.scores(), vec![0, 1]);
+        assert_eq!(game.remaining_chips(), 16);
+    }
+}
@@ -0,0 +1,1025 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    rngs::StdRng,
+    seq::SliceRandom,
+    Rng, SeedableRng,
+};
+
+use crate::model::{
+    GameMove, GameSpec, GameState, Goal, Point, TargetType, BOARD_SIZE, DIRECTIONS, ROBOT_COUNT,
+    WallBoard, WALL_MAP_SIZE,
+};
+use crate::solver::{self, SolutionCounting, VisitedSet};
+
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    pub seed: u64,
+    // optimal move counts to accept. None picks the farthest state.
+    pub moves: Option<RangeInclusive<usize>>,
+    // number of boards to try before giving up.
+    pub max_boards: usize,
+    // number of placements sampled per move count on each board.
+    pub candidates: usize,
+    // number of placements checked against `unique` and `constraints` before giving up.
+    pub max_attempts: usize,
+    // count optimal solutions this way and report them in `Puzzle::solutions`.
+    pub counting: Option<SolutionCounting>,
+    // only accept placements whose optimal solution is unique under `counting`.
+    pub unique: bool,
+    // every optimal solution must satisfy all of these.
+    pub constraints: Vec<Constraint>,
+    pub rules: BoardRules,
+}
+
+impl GeneratorConfig {
+    pub fn new(seed: u64) -> GeneratorConfig {
+        GeneratorConfig {
+            seed,
+            moves: None,
+            max_boards: 16,
+            candidates: 8,
+            max_attempts: 64,
+            counting: None,
+            unique: false,
+            constraints: Vec::new(),
+            rules: BoardRules::default(),
+        }
+    }
+
+    // checks the settings that depend on each other once all flags are applied.
+    // `generate` and the spec functions refuse a config that fails it.
+    pub fn check(&self) -> Result<(), String> {
+        if self.unique && self.counting.is_none() {
+            return Err("unique puzzles need a way to count solutions".to_string());
+        }
+        self.rules.check()
+    }
+
+    // applies one `--name=value` command line flag; Ok(false) if it isn't a generator flag.
+    pub fn apply_flag(&mut self, name: &str, value: &str) -> Result<bool, String> {
+        let number = |what: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("{} must be a number", what))
+        };
+        match name {
+            "--moves" => self.moves = Some(parse_moves(value)?),
+            "--unique" => {
+                self.counting = Some(SolutionCounting::ModuloIndependentMoves);
+                self.unique = true;
+            }
+            "--unique-strict" => {
+                self.counting = Some(SolutionCounting::Strict);
+                self.unique = true;
+            }
+            "--constraint" => self.constraints.push(Constraint::parse(value)?),
+            "--max-attempts" => self.max_attempts = number("max attempts")?,
+            "--goals" => match number("goals")? {
+                0 => return Err("goals must be at least 1".to_string()),
+                count => self.rules.goal_count = count,
+            },
+            "--edge-walls" => self.rules.edge_walls = number("edge walls")?,
+            "--center" => {
+                self.rules.center = match number("center")? {
+                    0 => CenterShape::None,
+                    size => CenterShape::Square(size),
+                }
+            }
+            "--goal-spacing" => self.rules.min_goal_spacing = number("goal spacing")? as i8,
+            "--max-cell-walls" => self.rules.max_cell_walls = number("max cell walls")?,
+            "--symmetric" => self.rules.quadrant_symmetry = true,
+            "--corners" => self.rules.corner_shapes = parse_corner_shapes(value)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+// "7", "4-6" or "10+"
+pub fn parse_moves(text: &str) -> Result<RangeInclusive<usize>, String> {
+    let parse = |x: &str| {
+        x.parse::<usize>()
+            .map_err(|_| format!("moves must be like 7, 4-6 or 10+, not {}", text))
+    };
+    if let Some(min) = text.strip_suffix('+') {
+        Ok(parse(min)?..=usize::MAX)
+    } else if let Some((min, max)) = text.split_once('-') {
+        let (min, max) = (parse(min)?, parse(max)?);
+        if min > max {
+            return Err(format!("moves range {} is reversed", text));
+        }
+        Ok(min..=max)
+    } else {
+        Ok(parse(text)?..=parse(text)?)
+    }
+}
+
+// "l:3,single:1"
+pub fn parse_corner_shapes(text: &str) -> Result<Vec<(CornerShape, u32)>, String> {
+    text.split(',')
+        .map(|entry| {
+            let (shape, weight) = entry.split_once(':').unwrap_or((entry, "1"));
+            let shape = match shape {
+                "l" => CornerShape::L,
+                "single" => CornerShape::Single,
+                _ => return Err(format!("unknown corner shape {}", shape)),
+            };
+            let weight = weight
+                .parse()
+                .map_err(|_| "corner weight must be a number".to_string())?;
+            Ok((shape, weight))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CornerShape {
+    // two walls meeting at a corner of the goal cell
+    L,
+    // one wall on a side of the goal cell
+    Single,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CenterShape {
+    None,
+    // walled-off square with this many cells per side in the middle of the board
+    Square(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct BoardRules {
+    pub goal_count: usize,
+    // relative weights of the wall shapes around each goal
+    pub corner_shapes: Vec<(CornerShape, u32)>,
+    // walls sticking out of each side of the board
+    pub edge_walls: usize,
+    pub center: CenterShape,
+    // goals are at least this far apart in Chebyshev distance
+    pub min_goal_spacing: i8,
+    // boards with a cell surrounded by more walls than this are rejected
+    pub max_cell_walls: usize,
+    // one quadrant is generated and rotated into the other three
+    pub quadrant_symmetry: bool,
+}
+
+impl BoardRules {
+    // checks the rules that depend on each other once all flags are applied.
+    pub fn check(&self) -> Result<(), String> {
+        if self.goal_count == 0 {
+            return Err("goals must be at least 1".to_string());
+        }
+        // goals are placed in groups of 4 along with their walls
+        if self.quadrant_symmetry && !self.goal_count.is_multiple_of(4) {
+            return Err(format!("symmetric boards need a multiple of 4 goals, not {}", self.goal_count));
+        }
+        // goals stay off the outer ring of cells and out of the center
+        if let CenterShape::Square(size) = self.center {
+            if size + 4 > BOARD_SIZE {
+                return Err(format!("the center can be at most {} cells wide, not {}", BOARD_SIZE - 4, size));
+            }
+        }
+        // at most one goal fits in each square of this many cells per side
+        let spacing = self.min_goal_spacing.max(1) as usize;
+        let room = (BOARD_SIZE - 2).div_ceil(spacing).pow(2);
+        if self.goal_count > room {
+            return Err(format!("at most {} goals fit {} cells apart, not {}", room, spacing, self.goal_count));
+        }
+        // the corner cells of the board already have two walls
+        if self.max_cell_walls < 2 {
+            return Err("max cell walls must be at least 2".to_string());
+        }
+        if self.corner_shapes.iter().all(|(_, weight)| *weight == 0) {
+            return Err("corner shape weights must not be all zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for BoardRules {
+    fn default() -> BoardRules {
+        BoardRules {
+            goal_count: 17,
+            corner_shapes: vec![(CornerShape::L, 1)],
+            edge_walls: 2,
+            center: CenterShape::Square(2),
+            min_goal_spacing: 2,
+            max_cell_walls: 2,
+            quadrant_symmetry: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Constraint {
+    // "robots>=N": at least N different robots move.
+    MovesRobots(usize),
+    // "target-last": other robots move first, then only the target robot.
+    TargetMovesLast,
+    // "blocker": the target robot is stopped by another robot at least once.
+    NeedsBlocker,
+}
+
+impl Constraint {
+    pub fn parse(text: &str) -> Result<Constraint, String> {
+        if let Some(count) = text.strip_prefix("robots>=") {
+            let count = count
+                .parse()
+                .map_err(|_| format!("invalid robot count in constraint: {}", text))?;
+            return Ok(Constraint::MovesRobots(count));
+        }
+        match text {
+            "target-last" => Ok(Constraint::TargetMovesLast),
+            "blocker" => Ok(Constraint::NeedsBlocker),
+            _ => Err(format!("unknown constraint: {}", text)),
+        }
+    }
+
+    pub fn holds(
+        &self,
+        spec: &GameSpec,
+        initial_state: &GameState,
+        solution: &[GameMove],
+    ) -> bool {
+        let mut states = vec![initial_state.clone()];
+        for game_move in solution {
+            states.push(spec.apply_move(states.last().unwrap(), game_move));
+        }
+        let target_robot = target_robot(spec, states.last().unwrap());
+
+        match self {
+            Constraint::MovesRobots(count) => {
+                solution
+                    .iter()
+                    .map(|game_move| game_move.robot_index)
+                    .unique()
+                    .count()
+                    >= *count
+            }
+            Constraint::TargetMovesLast => {
+                let first_target_move = solution
+                    .iter()
+                    .position(|game_move| game_move.robot_index as usize == target_robot);
+                match first_target_move {
+                    Some(first) => {
+                        first > 0
+                            && solution[first..]
+                                .iter()
+                                .all(|game_move| game_move.robot_index as usize == target_robot)
+                    }
+                    None => false,
+                }
+            }
+            Constraint::NeedsBlocker => {
+                solution
+                    .iter()
+                    .zip(states.iter().skip(1))
+                    .any(|(game_move, state)| {
+                        let position = state.robots[target_robot];
+                        game_move.robot_index as usize == target_robot
+                            && !spec.has_wall(position, game_move.direction)
+                            && state.robots.contains(&(position + Point::from(game_move.direction)))
+                    })
+            }
+        }
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Constraint::MovesRobots(count) => write!(f, "robots>={}", count),
+            Constraint::TargetMovesLast => write!(f, "target-last"),
+            Constraint::NeedsBlocker => write!(f, "blocker"),
+        }
+    }
+}
+
+fn target_robot(spec: &GameSpec, final_state: &GameState) -> usize {
+    let goal = &spec.goals[0];
+    let on_goal = final_state
+        .robots
+        .iter()
+        .position(|robot| *robot == goal.position)
+        .unwrap_or(0);
+    goal.target_type.robot_index(on_goal)
+}
+
+// constraints are checked against every optimal solution; placements with more
+// than this many are rejected unchecked.
+pub const SOLUTION_LIMIT: usize = 1000;
+
+// every optimal solution, or None if there are more than `limit`.
+fn all_solutions(paths: &solver::OptimalPaths, limit: usize) -> Option<Vec<Vec<GameMove>>> {
+    let solutions = paths.solutions(limit + 1);
+    (solutions.len() <= limit).then_some(solutions)
+}
+
+#[derive(Debug)]
+pub struct Puzzle {
+    // the seed of this board; `generate_spec` with it gives `spec` again.
+    pub seed: u64,
+    pub spec: GameSpec,
+    pub state: GameState,
+    pub moves: usize,
+    pub solutions: Option<usize>,
+}
+
+// what happened to the candidates when no puzzle was found.
+#[derive(Debug, Default)]
+pub struct GenerateFailure {
+    pub boards: usize,
+    pub boards_without_candidates: usize,
+    pub attempts: usize,
+    pub not_unique: usize,
+    // placements with more optimal solutions than `SOLUTION_LIMIT` to check constraints on
+    pub unchecked: usize,
+    // rejections per entry of `GeneratorConfig::constraints`
+    pub rejected_by: Vec<(Constraint, usize)>,
+}
+
+impl fmt::Display for GenerateFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "no puzzle found in {} boards ({} without a matching placement), {} placements checked",
+            self.boards, self.boards_without_candidates, self.attempts
+        )?;
+        if self.not_unique > 0 {
+            write!(f, ", {} not unique", self.not_unique)?;
+        }
+        if self.unchecked > 0 {
+            write!(f, ", {} with too many solutions to check", self.unchecked)?;
+        }
+        for (constraint, count) in &self.rejected_by {
+            write!(f, ", {} rejected by {}", count, constraint)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum GenerateError {
+    // the config can't produce a puzzle
+    InvalidConfig(String),
+    NoPuzzle(GenerateFailure),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenerateError::InvalidConfig(message) => write!(f, "{}", message),
+            GenerateError::NoPuzzle(failure) => write!(f, "{}", failure),
+        }
+    }
+}
+
+pub fn generate(config: &GeneratorConfig) -> Result<Puzzle, GenerateError> {
+    config.check().map_err(GenerateError::InvalidConfig)?;
+    let mut failure = GenerateFailure {
+        rejected_by: config.constraints.iter().map(|constraint| (*constraint, 0)).collect(),
+        ..Default::default()
+    };
+    for index in 0..config.max_boards {
+        let seed = board_seed(config.seed, index);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let spec = random_spec(&config.rules, &mut rng).map_err(GenerateError::InvalidConfig)?;
+        failure.boards += 1;
+        let candidates = candidate_states(&spec, config, &mut rng);
+        if candidates.is_empty() {
+            failure.boards_without_candidates += 1;
+        }
+        for (state, moves) in candidates {
+            let needs_solutions = config.counting.is_some() || !config.constraints.is_empty();
+            if needs_solutions {
+                if failure.attempts >= config.max_attempts {
+                    return Err(GenerateError::NoPuzzle(failure));
+                }
+                failure.attempts += 1;
+            }
+
+            let paths = if needs_solutions {
+                solver::OptimalPaths::new(&spec, &state)
+            } else {
+                None
+            };
+            let count = config
+                .counting
+                .and_then(|counting| Some(paths.as_ref()?.count(counting)));
+            if config.unique && count.is_some_and(|count| count != 1) {
+                failure.not_unique += 1;
+                continue;
+            }
+            let solutions = match &paths {
+                Some(paths) if !config.constraints.is_empty() => {
+                    match all_solutions(paths, SOLUTION_LIMIT) {
+                        Some(solutions) => solutions,
+                        None => {
+                            failure.unchecked += 1;
+                            continue;
+                        }
+                    }
+                }
+                _ => Vec::new(),
+            };
+            let rejected_by = config.constraints.iter().position(|constraint| {
+                !solutions
+                    .iter()
+                    .all(|solution| constraint.holds(&spec, &state, solution))
+            });
+            if let Some(i) = rejected_by {
+                failure.rejected_by[i].1 += 1;
+                continue;
+            }
+            return Ok(Puzzle {
+                seed,
+                spec,
+                state,
+                moves,
+                solutions: count,
+            });
+        }
+    }
+    Err(GenerateError::NoPuzzle(failure))
+}
+
+fn winning_states(spec: &GameSpec, goal_robot: usize) -> Vec<GameState> {
+    let mut vis = HashSet::new();
+    let mut q = VecDeque::new();
+
+    assert_eq!(spec.goals.len(), 1);
+    let goal_position = spec.goals[0].position;
+
+    vis.insert(goal_position);
+    q.push_back(goal_position);
+
+    while !q.is_empty() {
+        let position = q.pop_front().unwrap();
+        for direction in DIRECTIONS {
+            let next = position + Point::from(direction);
+            if next.r < 0
+                || next.r >= WALL_MAP_SIZE as i8
+                || next.c < 0
+                || next.c >= WALL_MAP_SIZE as i8
+            {
+                continue;
+            }
+            if spec.walls[next.r as usize][next.c as usize] {
+                continue;
+            }
+            if vis.contains(&next) {
+                continue;
+            }
+            vis.insert(next);
+            q.push_back(next);
+        }
+    }
+
+    let mut available_cells = Vec::new();
+    for r in 0..BOARD_SIZE {
+        for c in 0..BOARD_SIZE {
+            let r = r as i8;
+            let c = c as i8;
+            let wall_cell = Point::new(r * 2 + 1, c * 2 + 1);
+            let field_cell = Point::new(r, c);
+            if vis.contains(&wall_cell) && field_cell != goal_position {
+                available_cells.push(field_cell);
+            }
+        }
+    }
+
+    available_cells
+        .into_iter()
+        .permutations(ROBOT_COUNT - 1)
+        .map(|points| {
+            let mut robots = [goal_position; ROBOT_COUNT];
+            for (i, point) in points.into_iter().enumerate() {
+                if i < goal_robot {
+                    robots[i] = point
+                } else {
+                    robots[i + 1] = point
+                }
+            }
+            GameState { robots }
+        })
+        .collect()
+}
+
+fn all_winning_states(spec: &GameSpec) -> Vec<GameState> {
+    assert_eq!(spec.goals.len(), 1);
+    match spec.goals[0].target_type {
+        TargetType::Particular(target_robot) => winning_states(spec, target_robot),
+        TargetType::Any => (0..ROBOT_COUNT)
+            .flat_map(|robot_index| winning_states(spec, robot_index).into_iter())
+            .collect(),
+    }
+}
+
+fn is_acceptable_final_state(spec: &GameSpec, state: &GameState) -> bool {
+    state.robots.iter()
+    .map(|robot| {
+        spec.walls[robot.r as usize * 2 + 1][robot.c as usize * 2 + 2] as i64 +
+        spec.walls[robot.r as usize * 2 + 1][robot.c as usize * 2] as i64 +
+        spec.walls[robot.r as usize * 2 + 2][robot.c as usize * 2 + 1] as i64 +
+        spec.walls[robot.r as usize * 2][robot.c as usize * 2 + 1] as i64
+    })
+    .sum::<i64>() <= 0
+}
+
+// visits every state from which the goal is reachable, in order of its optimal move count.
+// states that only differ by a permutation of the non-target robots are visited once.
+pub(crate) fn reverse_search(spec: &GameSpec, mut visit: impl FnMut(&GameState, usize) -> bool) {
+    let mut vis = VisitedSet::full();
+    let mut q = VecDeque::new();
+
+    'mainloop: for winning_state in all_winning_states(spec) {
+        for equivalent_state in
+            spec.equivalent_states_particular(&winning_state, spec.goals[0].target_type.robot_index(0))
+        {
+            if vis.contains(equivalent_state.to_u32()) {
+                continue 'mainloop;
+            }
+        }
+        vis.insert(winning_state.to_u32());
+        q.push_back((winning_state, 0_u8));
+    }
+
+    while !q.is_empty() {
+        let (visiting_state, depth) = q.pop_front().unwrap();
+        if !visit(&visiting_state, depth as usize) {
+            return;
+        }
+
+        'mainloop: for next_state in spec.prev_states(&visiting_state) {
+            for equivalent_state in
+                spec.equivalent_states_particular(&next_state, spec.goals[0].target_type.robot_index(0))
+            {
+                if vis.contains(equivalent_state.to_u32()) {
+                    continue 'mainloop;
+                }
+            }
+            vis.insert(next_state.to_u32());
+            q.push_back((next_state, depth + 1));
+        }
+    }
+}
+
+// the acceptable state with the longest optimal solution, and its length.
+// None if no placement the goal can be reached from is acceptable.
+pub fn farthest_state(spec: &GameSpec) -> Option<(GameState, usize)> {
+    let mut result = None;
+    reverse_search(spec, |state, depth| {
+        if is_acceptable_final_state(spec, state) {
+            result = Some((state.clone(), depth));
+        }
+        true
+    });
+    result
+}
+
+pub fn reverse_bfs(spec: &GameSpec) -> Option<GameState> {
+    farthest_state(spec).map(|(state, _)| state)
+}
+
+// samples up to `config.candidates` acceptable states per layer, layers in random order.
+fn candidate_states(
+    spec: &GameSpec,
+    config: &GeneratorConfig,
+    rng: &mut impl Rng,
+) -> Vec<(GameState, usize)> {
+    let moves = config.moves.clone().unwrap_or(0..=usize::MAX);
+    // reservoir sample per layer: (chosen states, number of states seen)
+    let mut layers: Vec<(Vec<GameState>, usize)> = Vec::new();
+    reverse_search(spec, |state, depth| {
+        if depth > *moves.end() {
+            return false;
+        }
+        if depth < *moves.start() || !is_acceptable_final_state(spec, state) {
+            return true;
+        }
+        let layer = depth - moves.start();
+        if layers.len() <= layer {
+            layers.resize(layer + 1, (Vec::new(), 0));
+        }
+        let (chosen, seen) = &mut layers[layer];
+        *seen += 1;
+        if chosen.len() < config.candidates {
+            chosen.push(state.clone());
+        } else {
+            let index = rng.gen_range(0..*seen);
+            if index < config.candidates {
+                chosen[index] = state.clone();
+            }
+        }
+        true
+    });
+
+    let mut layers: Vec<(usize, Vec<GameState>)> = layers
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (chosen, _))| !chosen.is_empty())
+        .map(|(layer, (chosen, _))| (moves.start() + layer, chosen))
+        .collect();
+    if config.moves.is_none() {
+        layers = layers.into_iter().last().into_iter().collect();
+    }
+    layers.shuffle(rng);
+    layers
+        .into_iter()
+        .flat_map(|(depth, chosen)| chosen.into_iter().map(move |state| (state, depth)))
+        .collect()
+}
+
+// the seed of the board tried at `index`. the first board uses the seed itself,
+// so `generate_spec` gives the first board `generate` tries.
+pub fn board_seed(seed: u64, index: usize) -> u64 {
+    if index == 0 {
+        return seed;
+    }
+    // splitmix64
+    let mut z = seed.wrapping_add((index as u64).wrapping_mul(0x9e3779b97f4a7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+pub fn generate_spec(config: &GeneratorConfig) -> Result<GameSpec, String> {
+    config.check()?;
+    random_spec(&config.rules, &mut StdRng::seed_from_u64(config.seed))
+}
+
+// rotates by 90 degrees around the center of a square of the given size.
+fn rotate(point: Point, size: usize) -> Point {
+    Point::new(point.c, size as i8 - 1 - point.r)
+}
+
+fn rotations(point: Point, size: usize, symmetric: bool) -> Vec<Point> {
+    let count = if symmetric { 4 } else { 1 };
+    let mut points = vec![point];
+    while points.len() < count {
+        points.push(rotate(*points.last().unwrap(), size));
+    }
+    points
+}
+
+fn random_spec(rules: &BoardRules, rng: &mut impl Rng) -> Result<GameSpec, String> {
+    let (walls, goals) = random_board(rules, rng)?;
+    let target_index: usize = rng.gen::<usize>() % rules.goal_count;
+    Ok(GameSpec::new(walls, vec![goal(rules, &goals, target_index)]))
+}
+
+fn goal(rules: &BoardRules, goals: &[Point], index: usize) -> Goal {
+    let target_type = if index < (rules.goal_count - 1) {
+        TargetType::Particular(index * ROBOT_COUNT / (rules.goal_count - 1))
+    } else {
+        TargetType::Any
+    };
+    Goal { position: goals[index], target_type }
+}
+
+// one spec per goal spot of the board generated from the seed.
+pub fn generate_goal_specs(config: &GeneratorConfig) -> Result<Vec<GameSpec>, String> {
+    config.check()?;
+    let (walls, goals) = random_board(&config.rules, &mut StdRng::seed_from_u64(config.seed))?;
+    Ok((0..goals.len())
+        .map(|index| GameSpec::new(walls, vec![goal(&config.rules, &goals, index)]))
+        .collect())
+}
+
+// boards thrown away for their walls before `random_board` gives up
+const MAX_BOARD_TRIES: usize = 1000;
+// goal spots sampled for one goal before the board is started over
+const MAX_GOAL_SAMPLES: usize = 1000;
+
+fn random_board(rules: &BoardRules, rng: &mut impl Rng) -> Result<(WallBoard, Vec<Point>), String> {
+    let symmetric = rules.quadrant_symmetry;
+    let center = match rules.center {
+        CenterShape::None => 0..0,
+        CenterShape::Square(size) => ((BOARD_SIZE - size) / 2) as i8..((BOARD_SIZE + size) / 2) as i8,
+    };
+    let corner_shapes = WeightedIndex::new(rules.corner_shapes.iter().map(|(_, weight)| *weight))
+        .map_err(|_| "corner shape weights must not be all zero".to_string())?;
+
+    'mainloop: for _ in 0..MAX_BOARD_TRIES {
+        const DIRECTION_POINTS: [Point; 4] = [  // needs to be clockwise or counter-clock wise order
+            Point::new(-1, 0),
+            Point::new(0, 1),
+            Point::new(1, 0),
+            Point::new(0, -1),
+        ];
+
+        let mut walls = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+        let mut set_wall = |wall_position: Point| {
+            for wall_position in rotations(wall_position, WALL_MAP_SIZE, symmetric) {
+                walls[wall_position.r as usize][wall_position.c as usize] = true;
+            }
+        };
+        center.clone().for_each(|r| {
+            center.clone().for_each(|c| {
+                let center = Point::new(r * 2 + 1, c * 2 + 1);
+                DIRECTION_POINTS.iter().for_each(|dp| set_wall(center + *dp));
+            });
+        });
+        (0..WALL_MAP_SIZE as i8).for_each(|i| {
+            set_wall(Point::new(0, i));
+            set_wall(Point::new(WALL_MAP_SIZE as i8 - 1, i));
+            set_wall(Point::new(i, 0));
+            set_wall(Point::new(i, WALL_MAP_SIZE as i8 - 1));
+        });
+
+        let mut goals: Vec<Point> = Vec::new();
+        while goals.len() < rules.goal_count {
+            let mut samples = 0;
+            'sampling: loop {
+                samples += 1;
+                if samples > MAX_GOAL_SAMPLES {
+                    continue 'mainloop;
+                }
+                let candidate = Point::new(rng.gen_range(0..BOARD_SIZE as i8), rng.gen_range(0..BOARD_SIZE as i8));
+                if center.contains(&candidate.r) && center.contains(&candidate.c) {
+                    continue 'sampling;
+                }
+                if candidate.r == 0 || candidate.r + 1 == BOARD_SIZE as i8 || candidate.c == 0 || candidate.c + 1 == BOARD_SIZE as i8 {
+                    continue 'sampling;
+                }
+
+                let candidates = rotations(candidate, BOARD_SIZE, symmetric);
+                let too_close = |a: &Point, b: &Point| a.chebyshev(b) < rules.min_goal_spacing;
+                if candidates.iter().any(|candidate| goals.iter().any(|other| too_close(candidate, other))) {
+                    continue 'sampling;
+                }
+                if candidates.iter().tuple_combinations().any(|(a, b)| too_close(a, b)) {
+                    continue 'sampling;
+                }
+                goals.extend(candidates);
+                let shape = if rules.corner_shapes.len() > 1 {
+                    rules.corner_shapes[corner_shapes.sample(rng)].0
+                } else {
+                    rules.corner_shapes[0].0
+                };
+                let wall_count = match shape {
+                    CornerShape::L => 2,
+                    CornerShape::Single => 1,
+                };
+                let wall_direction = rng.gen::<usize>() % 4;
+                (0..wall_count).for_each(|offset| {
+                    let direction_index = (wall_direction + offset) % 4;
+                    set_wall(candidate * 2 + Point::new(1, 1) + DIRECTION_POINTS[direction_index]);
+                });
+                break 'sampling;
+            }
+        }
+
+        (0..rules.edge_walls).for_each(|_i| {
+            let x = rng.gen::<usize>() % (BOARD_SIZE - 1);
+            set_wall(Point::new(1, x as i8 * 2 + 2));
+            if symmetric {
+                return;
+            }
+
+            let x = rng.gen::<usize>() % (BOARD_SIZE - 1);
+            set_wall(Point::new(WALL_MAP_SIZE as i8 - 2, x as i8 * 2 + 2));
+
+            let x = rng.gen::<usize>() % (BOARD_SIZE - 1);
+            set_wall(Point::new(x as i8 * 2 + 2, 1));
+
+            let x = rng.gen::<usize>() % (BOARD_SIZE - 1);
+            set_wall(Point::new(x as i8 * 2 + 2, WALL_MAP_SIZE as i8 - 2));
+        });
+
+        for r in 0..BOARD_SIZE as i8 {
+            for c in 0..BOARD_SIZE as i8 {
+                if center.contains(&r) && center.contains(&c) {
+                    continue;
+                }
+                let center = Point::new(r * 2 + 1, c * 2 + 1);
+                let surrounding_walls: usize = DIRECTION_POINTS.iter().map(|dp| {
+                    let wall_position = center + *dp;
+                    if walls[wall_position.r as usize][wall_position.c as usize] { 1 } else { 0 }
+                }).sum();
+                if surrounding_walls > rules.max_cell_walls {
+                    continue 'mainloop;
+                }
+            }
+        }
+
+        return Ok((walls, goals));
+    }
+    Err(format!("no board follows the rules after {} tries", MAX_BOARD_TRIES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{small_spec, ID};
+    use crate::model::Direction;
+    use crate::serialize;
+
+    #[test]
+    fn test_same_seed_same_spec() {
+        for seed in [0, 1, 42, u64::MAX] {
+            let a = generate_spec(&GeneratorConfig::new(seed)).unwrap();
+            let b = generate_spec(&GeneratorConfig::new(seed)).unwrap();
+            assert_eq!(a.walls, b.walls);
+            assert_eq!(a.goals, b.goals);
+        }
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        assert_eq!(parse_moves("7"), Ok(7..=7));
+        assert_eq!(parse_moves("4-6"), Ok(4..=6));
+        assert_eq!(parse_moves("10+"), Ok(10..=usize::MAX));
+        assert!(parse_moves("9-4").is_err());
+        assert!(parse_moves("x").is_err());
+    }
+
+    #[test]
+    fn test_constraint_parse() {
+        for text in ["robots>=3", "target-last", "blocker"] {
+            assert_eq!(Constraint::parse(text).unwrap().to_string(), text);
+        }
+        assert!(Constraint::parse("robots>=x").is_err());
+        assert!(Constraint::parse("unknown").is_err());
+    }
+
+    #[test]
+    fn test_all_solutions_limit() {
+        let goal = Goal {
+            position: Point::new(1, 1),
+            target_type: TargetType::Any,
+        };
+        let spec = small_spec(goal);
+        // every robot is one move from the middle, with the one across as its blocker
+        let state = GameState {
+            robots: [
+                Point::new(0, 1),
+                Point::new(2, 1),
+                Point::new(1, 0),
+                Point::new(1, 2),
+            ],
+        };
+        let paths = solver::OptimalPaths::new(&spec, &state).unwrap();
+        let count = paths.count(SolutionCounting::Strict);
+        assert_eq!(count, 4);
+        assert_eq!(all_solutions(&paths, count).unwrap().len(), count);
+        // a placement whose solutions can't all be checked isn't checked at all
+        assert_eq!(all_solutions(&paths, count - 1), None);
+    }
+
+    #[test]
+    fn test_unique_needs_counting() {
+        let mut config = GeneratorConfig::new(3);
+        config.unique = true;
+        assert!(config.check().is_err());
+        assert!(matches!(
+            generate(&config),
+            Err(GenerateError::InvalidConfig(_))
+        ));
+        config.apply_flag("--unique", "").unwrap();
+        assert_eq!(config.check(), Ok(()));
+    }
+
+    #[test]
+    fn test_constraint_holds() {
+        let (spec, state) = serialize::load(ID);
+        let target = spec.goals[0].target_type.robot_index(0);
+        let other = (target + 1) % ROBOT_COUNT;
+        let target_move = GameMove { robot_index: target as u8, direction: Direction::Up };
+        let other_move = GameMove { robot_index: other as u8, direction: Direction::Left };
+
+        let target_last = [other_move.clone(), target_move.clone()];
+        let target_first = [target_move.clone(), other_move.clone()];
+        assert!(Constraint::MovesRobots(2).holds(&spec, &state, &target_last));
+        assert!(!Constraint::MovesRobots(3).holds(&spec, &state, &target_last));
+        assert!(Constraint::TargetMovesLast.holds(&spec, &state, &target_last));
+        assert!(!Constraint::TargetMovesLast.holds(&spec, &state, &target_first));
+        assert!(!Constraint::TargetMovesLast.holds(&spec, &state, &[target_move]));
+    }
+
+    #[test]
+    fn test_goal_count() {
+        let mut config = GeneratorConfig::new(3);
+        assert_eq!(generate_goal_specs(&config).unwrap().len(), 17);
+        assert_eq!(config.apply_flag("--goals", "5"), Ok(true));
+        assert_eq!(config.rules.check(), Ok(()));
+        assert_eq!(generate_goal_specs(&config).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_library_calls_check_config() {
+        // library callers set the rules directly, without the flag parser
+        let mut config = GeneratorConfig::new(3);
+        config.rules.goal_count = 0;
+        assert!(matches!(
+            generate(&config),
+            Err(GenerateError::InvalidConfig(_))
+        ));
+        assert!(generate_spec(&config).is_err());
+        assert!(generate_goal_specs(&config).is_err());
+    }
+
+    #[test]
+    fn test_zero_goals() {
+        let mut config = GeneratorConfig::new(3);
+        assert!(config.apply_flag("--goals", "0").is_err());
+        assert_eq!(config.rules.goal_count, 17);
+        config.rules.goal_count = 0;
+        assert!(config.rules.check().is_err());
+    }
+
+    #[test]
+    fn test_impossible_rules() {
+        let rejected = |flag: &str, value: &str| {
+            let mut config = GeneratorConfig::new(3);
+            config.apply_flag(flag, value).unwrap();
+            config.rules.check().is_err()
+        };
+        assert!(rejected("--center", "13"));
+        assert!(rejected("--center", "17"));
+        assert!(!rejected("--center", "12"));
+        assert!(rejected("--max-cell-walls", "1"));
+        assert!(rejected("--goal-spacing", "5"));
+        assert!(rejected("--corners", "l:0,single:0"));
+    }
+
+    #[test]
+    fn test_board_retries_are_capped() {
+        // 16 goals 4 cells apart only fit in one exact grid, which sampling never finds
+        let mut config = GeneratorConfig::new(3);
+        config.rules.goal_count = 16;
+        config.rules.min_goal_spacing = 4;
+        assert_eq!(config.rules.check(), Ok(()));
+        assert!(generate_spec(&config).is_err());
+        assert!(generate_goal_specs(&config).is_err());
+    }
+
+    #[test]
+    fn test_symmetric_goal_count() {
+        let mut config = GeneratorConfig::new(3);
+        config.apply_flag("--symmetric", "").unwrap();
+        assert!(config.rules.check().is_err());
+        config.apply_flag("--goals", "8").unwrap();
+        assert_eq!(config.rules.check(), Ok(()));
+    }
+
+    #[test]
+    fn test_quadrant_symmetry() {
+        let mut config = GeneratorConfig::new(3);
+        config.rules.goal_count = 16;
+        config.rules.quadrant_symmetry = true;
+        config.rules.corner_shapes = vec![(CornerShape::L, 3), (CornerShape::Single, 1)];
+        let spec = generate_spec(&config).unwrap();
+        for r in 0..WALL_MAP_SIZE {
+            for c in 0..WALL_MAP_SIZE {
+                let rotated = rotate(Point::new(r as i8, c as i8), WALL_MAP_SIZE);
+                assert_eq!(spec.walls[r][c], spec.walls[rotated.r as usize][rotated.c as usize]);
+            }
+        }
+
+        // every goal rotates onto another goal, so no goal walls are left without their goal
+        let goals: HashSet<Point> = generate_goal_specs(&config)
+            .unwrap()
+            .iter()
+            .map(|spec| spec.goals[0].position)
+            .collect();
+        assert_eq!(goals.len(), 16);
+        for goal in &goals {
+            assert!(goals.contains(&rotate(*goal, BOARD_SIZE)));
+        }
+    }
+
+    #[test]
+    fn test_no_acceptable_state() {
+        // the middle of the 3x3 corner is the only cell without walls, too few for four robots
+        let goal = Goal {
+            position: Point::new(0, 0),
+            target_type: TargetType::Particular(0),
+        };
+        assert_eq!(farthest_state(&small_spec(goal)), None);
+    }
+
+    #[test]
+    fn test_board_seeds() {
+        assert_eq!(board_seed(7, 0), 7);
+        let seeds: HashSet<u64> = (0..16).map(|index| board_seed(7, index)).collect();
+        assert_eq!(seeds.len(), 16);
+
+        // later boards differ from the first one instead of repeating it
+        let first = generate_spec(&GeneratorConfig::new(board_seed(7, 0))).unwrap();
+        let second = generate_spec(&GeneratorConfig::new(board_seed(7, 1))).unwrap();
+        assert_ne!(first.walls, second.walls);
+    }
+
+    #[test]
+    fn test_different_seed_different_spec() {
+        let a = generate_spec(&GeneratorConfig::new(1)).unwrap();
+        let b = generate_spec(&GeneratorConfig::new(2)).unwrap();
+        assert_ne!(a.walls, b.walls);
+    }
+}
@@ -0,0 +1,270 @@
+// long-running search for the longest puzzles over many seeded boards.
+// a job is one goal spot of one board; progress is checkpointed as plain text.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::generator::{self, GeneratorConfig};
+use crate::serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub moves: usize,
+    pub seed: u64,
+    pub goal: usize,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunt {
+    pub first_seed: u64,
+    pub goals_per_board: usize,
+    pub leaderboard_size: usize,
+    pub leaderboard: Vec<Record>,
+    // every job before this one is finished
+    frontier: u64,
+    // finished jobs after the frontier
+    done: BTreeSet<u64>,
+    // next job to hand out; not checkpointed
+    issued: u64,
+}
+
+impl Hunt {
+    pub fn new(first_seed: u64, goals_per_board: usize, leaderboard_size: usize) -> Hunt {
+        Hunt {
+            first_seed,
+            goals_per_board,
+            leaderboard_size,
+            leaderboard: Vec::new(),
+            frontier: 0,
+            done: BTreeSet::new(),
+            issued: 0,
+        }
+    }
+
+    // (seed, goal index) of the next unfinished job that is not handed out yet.
+    pub fn take_job(&mut self) -> (u64, usize) {
+        self.issued = self.issued.max(self.frontier);
+        while self.done.contains(&self.issued) {
+            self.issued += 1;
+        }
+        let job = self.issued;
+        self.issued += 1;
+        self.job_to_board(job)
+    }
+
+    // returns true if the record made it to the leaderboard. a job without a
+    // record is finished all the same; a seed and goal that are no job of this
+    // hunt are an error.
+    pub fn finish_job(
+        &mut self,
+        seed: u64,
+        goal: usize,
+        record: Option<Record>,
+    ) -> Result<bool, String> {
+        let job = seed
+            .checked_sub(self.first_seed)
+            .filter(|_| goal < self.goals_per_board)
+            .and_then(|board| board.checked_mul(self.goals_per_board as u64))
+            .and_then(|job| job.checked_add(goal as u64))
+            .ok_or_else(|| format!("seed {} goal {} is not a job of this hunt", seed, goal))?;
+        self.done.insert(job);
+        while self.done.remove(&self.frontier) {
+            self.frontier += 1;
+        }
+        let Some(record) = record else {
+            return Ok(false);
+        };
+
+        let position = self
+            .leaderboard
+            .iter()
+            .position(|other| other.moves < record.moves)
+            .unwrap_or(self.leaderboard.len());
+        if position >= self.leaderboard_size {
+            return Ok(false);
+        }
+        self.leaderboard.insert(position, record);
+        self.leaderboard.truncate(self.leaderboard_size);
+        Ok(true)
+    }
+
+    // a checkpoint only resumes with the settings it was started with: other
+    // seeds or goal counts would give its finished jobs to other boards.
+    pub fn check_settings(&self, other: &Hunt) -> Result<(), String> {
+        let settings = [
+            ("first seed", self.first_seed, other.first_seed),
+            (
+                "goals per board",
+                self.goals_per_board as u64,
+                other.goals_per_board as u64,
+            ),
+            (
+                "leaderboard size",
+                self.leaderboard_size as u64,
+                other.leaderboard_size as u64,
+            ),
+        ];
+        for (name, checkpoint, given) in settings {
+            if checkpoint != given {
+                return Err(format!(
+                    "the checkpoint has a {} of {}, not {}",
+                    name, checkpoint, given
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finished_jobs(&self) -> u64 {
+        self.frontier + self.done.len() as u64
+    }
+
+    fn job_to_board(&self, job: u64) -> (u64, usize) {
+        let goals = self.goals_per_board as u64;
+        (self.first_seed + job / goals, (job % goals) as usize)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = format!(
+            "first_seed {}\ngoals {}\nleaderboard_size {}\nfrontier {}\n",
+            self.first_seed, self.goals_per_board, self.leaderboard_size, self.frontier
+        );
+        for job in &self.done {
+            text += &format!("done {}\n", job);
+        }
+        for record in &self.leaderboard {
+            text += &format!(
+                "record {} {} {} {}\n",
+                record.moves, record.seed, record.goal, record.id
+            );
+        }
+        // write then rename so an interrupted save keeps the previous checkpoint.
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, text)?;
+        fs::rename(&tmp, path)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Hunt> {
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid checkpoint line: {}", line),
+            )
+        };
+        let mut hunt = Hunt::new(0, 0, 0);
+        for line in fs::read_to_string(path)?.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |i: usize| -> io::Result<u64> {
+                fields
+                    .get(i)
+                    .and_then(|field| field.parse().ok())
+                    .ok_or_else(|| invalid(line))
+            };
+            match fields.first() {
+                Some(&"first_seed") => hunt.first_seed = number(1)?,
+                Some(&"goals") => hunt.goals_per_board = number(1)? as usize,
+                Some(&"leaderboard_size") => hunt.leaderboard_size = number(1)? as usize,
+                Some(&"frontier") => hunt.frontier = number(1)?,
+                Some(&"done") => {
+                    hunt.done.insert(number(1)?);
+                }
+                Some(&"record") => hunt.leaderboard.push(Record {
+                    moves: number(1)? as usize,
+                    seed: number(2)?,
+                    goal: number(3)? as usize,
+                    id: fields.get(4).ok_or_else(|| invalid(line))?.to_string(),
+                }),
+                None => {}
+                Some(_) => return Err(invalid(line)),
+            }
+        }
+        if hunt.goals_per_board == 0 {
+            return Err(invalid("missing goals"));
+        }
+        Ok(hunt)
+    }
+}
+
+// runs the reverse search for one goal spot of one board; None if the board
+// has no acceptable placement for it.
+pub fn run_job(config: &GeneratorConfig, goal: usize) -> Option<Record> {
+    // a board the rules can't produce has no record either
+    let spec = &generator::generate_goal_specs(config).ok()?[goal];
+    let (state, moves) = generator::farthest_state(spec)?;
+    Some(Record {
+        moves,
+        seed: config.seed,
+        goal,
+        id: serialize::dump(spec, &state),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(moves: usize, seed: u64, goal: usize) -> Record {
+        Record {
+            moves,
+            seed,
+            goal,
+            id: format!("id{}x{}", seed, goal),
+        }
+    }
+
+    #[test]
+    fn test_jobs_and_leaderboard() {
+        let mut hunt = Hunt::new(10, 3, 2);
+        let jobs: Vec<(u64, usize)> = (0..4).map(|_| hunt.take_job()).collect();
+        assert_eq!(jobs, vec![(10, 0), (10, 1), (10, 2), (11, 0)]);
+
+        assert_eq!(hunt.finish_job(10, 1, Some(record(5, 10, 1))), Ok(true));
+        assert_eq!(hunt.finish_job(11, 0, Some(record(7, 11, 0))), Ok(true));
+        assert_eq!(hunt.finish_job(10, 2, Some(record(3, 10, 2))), Ok(false));
+        assert_eq!(hunt.finish_job(10, 0, Some(record(6, 10, 0))), Ok(true));
+        assert_eq!(hunt.finished_jobs(), 4);
+        // a board without an acceptable placement still finishes its job
+        assert_eq!(hunt.take_job(), (11, 1));
+        assert_eq!(hunt.finish_job(11, 1, None), Ok(false));
+        assert_eq!(hunt.finished_jobs(), 5);
+        // seeds before the first one and goals past the last one are no jobs
+        assert!(hunt.finish_job(9, 0, None).is_err());
+        assert!(hunt.finish_job(12, 3, None).is_err());
+        assert!(hunt.finish_job(u64::MAX, 0, None).is_err());
+        assert_eq!(hunt.finished_jobs(), 5);
+        assert_eq!(
+            hunt.leaderboard.iter().map(|r| r.moves).collect::<Vec<_>>(),
+            vec![7, 6]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        let mut hunt = Hunt::new(5, 17, 10);
+        for _ in 0..3 {
+            hunt.take_job();
+        }
+        hunt.finish_job(5, 0, Some(record(12, 5, 0))).unwrap();
+        hunt.finish_job(5, 2, Some(record(9, 5, 2))).unwrap();
+
+        let path = std::env::temp_dir().join(format!("hunt-{}.txt", std::process::id()));
+        hunt.save(&path).unwrap();
+        let mut loaded = Hunt::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.leaderboard, hunt.leaderboard);
+        assert_eq!(loaded.check_settings(&Hunt::new(5, 17, 10)), Ok(()));
+        assert_eq!(
+            loaded.check_settings(&Hunt::new(6, 17, 10)),
+            Err("the checkpoint has a first seed of 5, not 6".to_string())
+        );
+        assert!(loaded.check_settings(&Hunt::new(5, 16, 10)).is_err());
+        assert_eq!(loaded.finished_jobs(), 2);
+        // the unfinished job is handed out again after a restart.
+        assert_eq!(loaded.take_job(), (5, 1));
+        assert_eq!(loaded.take_job(), (5, 3));
+    }
+}
@@ -0,0 +1,254 @@
+// json form of a single-goal puzzle, for callers that don't speak kaseken ids.
+// walls are listed per cell side; the board edge is always walled.
+
+use serde::{Deserialize, Serialize};
+
+use crate::model::{
+    Direction, GameMove, GameSpec, GameState, Goal, Point, TargetType, WallBoard, BOARD_SIZE,
+    ROBOT_COUNT, WALL_MAP_SIZE,
+};
+use crate::solver::{Solution, SolveError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Wall {
+    pub position: Point,
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    pub position: Point,
+    // robot index; absent for the wild target
+    #[serde(default)]
+    pub robot: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    pub walls: Vec<Wall>,
+    pub robots: [Point; ROBOT_COUNT],
+    pub goal: Target,
+}
+
+fn on_board(position: Point) -> bool {
+    (0..BOARD_SIZE as i8).contains(&position.r) && (0..BOARD_SIZE as i8).contains(&position.c)
+}
+
+impl From<&Goal> for Target {
+    fn from(goal: &Goal) -> Target {
+        Target {
+            position: goal.position,
+            robot: match goal.target_type {
+                TargetType::Any => None,
+                TargetType::Particular(robot_index) => Some(robot_index),
+            },
+        }
+    }
+}
+
+// inner walls of the board, each listed once from the cell above or to the left of it.
+pub fn walls(spec: &GameSpec) -> Vec<Wall> {
+    let mut walls = Vec::new();
+    for r in 0..BOARD_SIZE as i8 {
+        for c in 0..BOARD_SIZE as i8 {
+            let position = Point::new(r, c);
+            let inner = [
+                (Direction::Down, r + 1 < BOARD_SIZE as i8),
+                (Direction::Right, c + 1 < BOARD_SIZE as i8),
+            ];
+            for (direction, inside) in inner {
+                if inside && spec.has_wall(position, direction) {
+                    walls.push(Wall {
+                        position,
+                        direction,
+                    });
+                }
+            }
+        }
+    }
+    walls
+}
+
+impl Puzzle {
+    // the puzzle for the first goal of the spec.
+    pub fn from_game(spec: &GameSpec, state: &GameState) -> Puzzle {
+        Puzzle {
+            walls: walls(spec),
+            robots: state.robots,
+            goal: Target::from(&spec.goals[0]),
+        }
+    }
+
+    pub fn to_game(&self) -> Result<(GameSpec, GameState), String> {
+        let mut walls: WallBoard = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+        walls[0] = [true; WALL_MAP_SIZE];
+        walls[WALL_MAP_SIZE - 1] = [true; WALL_MAP_SIZE];
+        for row in walls.iter_mut() {
+            row[0] = true;
+            row[WALL_MAP_SIZE - 1] = true;
+        }
+        for wall in &self.walls {
+            if !on_board(wall.position) {
+                return Err(format!("wall outside the board at {:?}", wall.position));
+            }
+            let wall_position = wall.position * 2 + Point::new(1, 1) + Point::from(wall.direction);
+            walls[wall_position.r as usize][wall_position.c as usize] = true;
+        }
+
+        for (robot_index, robot) in self.robots.iter().enumerate() {
+            if !on_board(*robot) {
+                return Err(format!("robot {} is outside the board", robot_index));
+            }
+            if self.robots[..robot_index].contains(robot) {
+                return Err(format!(
+                    "robot {} shares a cell with another robot",
+                    robot_index
+                ));
+            }
+        }
+        if !on_board(self.goal.position) {
+            return Err("goal is outside the board".to_string());
+        }
+        let target_type = match self.goal.robot {
+            None => TargetType::Any,
+            Some(robot_index) if robot_index < ROBOT_COUNT => TargetType::Particular(robot_index),
+            Some(robot_index) => return Err(format!("unknown goal robot {}", robot_index)),
+        };
+
+        let goal = Goal {
+            position: self.goal.position,
+            target_type,
+        };
+        let state = GameState {
+            robots: self.robots,
+        };
+        Ok((GameSpec::new(walls, vec![goal]), state))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolveStatus {
+    Solved,
+    Unsolvable,
+    ParseError,
+    Timeout,
+    StateLimit,
+}
+
+// one line of batch output; moves and move_count are null unless solved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolveReport {
+    pub id: String,
+    pub status: SolveStatus,
+    pub move_count: Option<usize>,
+    pub moves: Option<Vec<GameMove>>,
+    pub solve_ms: f64,
+    pub explored_states: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SolveReport {
+    pub fn new(id: &str, result: &Result<Solution, SolveError>) -> SolveReport {
+        let (status, stats) = match result {
+            Ok(solution) => (SolveStatus::Solved, Some(solution.stats)),
+            Err(SolveError::Unsolvable(_)) => (SolveStatus::Unsolvable, None),
+            Err(SolveError::NoSolution(stats)) => (SolveStatus::Unsolvable, Some(*stats)),
+            Err(SolveError::TimedOut(stats)) => (SolveStatus::Timeout, Some(*stats)),
+            Err(SolveError::StateLimit(stats)) => (SolveStatus::StateLimit, Some(*stats)),
+        };
+        let moves = result.as_ref().ok().map(|solution| solution.moves.clone());
+        SolveReport {
+            id: id.to_string(),
+            status,
+            move_count: moves.as_ref().map(Vec::len),
+            moves,
+            solve_ms: stats.map_or(0.0, |stats| stats.elapsed.as_secs_f64() * 1000.0),
+            explored_states: stats.map_or(0, |stats| stats.explored_states),
+            error: result.as_ref().err().map(ToString::to_string),
+        }
+    }
+
+    pub fn parse_error(id: &str, message: String) -> SolveReport {
+        SolveReport {
+            id: id.to_string(),
+            status: SolveStatus::ParseError,
+            move_count: None,
+            moves: None,
+            solve_ms: 0.0,
+            explored_states: 0,
+            error: Some(message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::ID;
+    use crate::model::DIRECTIONS;
+    use crate::serialize;
+    use crate::solver::SolveStats;
+    use std::time::Duration;
+
+    #[test]
+    fn test_puzzle_roundtrip() {
+        let (spec, state) = serialize::load(ID);
+        let puzzle = Puzzle::from_game(&spec, &state);
+        let text = serde_json::to_string(&puzzle).unwrap();
+        let parsed: Puzzle = serde_json::from_str(&text).unwrap();
+        let (loaded_spec, loaded_state) = parsed.to_game().unwrap();
+
+        assert_eq!(loaded_state, state);
+        assert_eq!(loaded_spec.goals, spec.goals[..1]);
+        for r in 0..BOARD_SIZE as i8 {
+            for c in 0..BOARD_SIZE as i8 {
+                for direction in DIRECTIONS {
+                    let position = Point::new(r, c);
+                    assert_eq!(
+                        loaded_spec.has_wall(position, direction),
+                        spec.has_wall(position, direction)
+                    );
+                }
+            }
+        }
+
+        let mut bad = puzzle.clone();
+        bad.robots[1] = bad.robots[0];
+        assert!(bad.to_game().is_err());
+    }
+
+    #[test]
+    fn test_solve_report() {
+        let moves = vec![GameMove {
+            robot_index: 2,
+            direction: Direction::Left,
+        }];
+        let stats = SolveStats {
+            explored_states: 17,
+            elapsed: Duration::from_millis(3),
+        };
+        let solved = SolveReport::new(
+            ID,
+            &Ok(Solution {
+                moves: moves.clone(),
+                stats,
+            }),
+        );
+        let value = serde_json::to_value(&solved).unwrap();
+        assert_eq!(value["status"], "solved");
+        assert_eq!(value["move_count"], 1);
+        assert_eq!(value["moves"][0]["direction"], "Left");
+        assert_eq!(value["explored_states"], 17);
+        assert!(value.get("error").is_none());
+
+        let timeout = SolveReport::new(ID, &Err(SolveError::TimedOut(stats)));
+        assert_eq!(timeout.status, SolveStatus::Timeout);
+        assert_eq!(timeout.moves, None);
+        let text =
+            serde_json::to_string(&SolveReport::parse_error("x", "bad".to_string())).unwrap();
+        assert!(text.contains(r#""status":"parse_error""#));
+        assert!(text.contains(r#""moves":null"#));
+    }
+}
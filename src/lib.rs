@@ -1,5 +1,22 @@
+pub mod analysis;
+pub mod batch;
+pub mod bidding;
+pub mod cli;
+pub mod explain;
+pub mod game;
+pub mod generator;
+pub mod hunt;
+pub mod json;
 pub mod model;
+pub mod play;
+pub mod render;
+#[cfg(feature = "native")]
+pub mod room;
 pub mod serialize;
+#[cfg(feature = "native")]
+pub mod service;
 pub mod solver;
 
 mod algorithm;
+#[cfg(test)]
+mod fixtures;
@@ -1,16 +1,19 @@
+use std::collections::{HashSet, VecDeque};
 use std::convert::From;
 use std::hash::Hash;
 use std::{cmp, ops};
 use std::sync::LazyLock;
 use std::{array, cmp::min};
 
+use serde::{Deserialize, Serialize};
+
 use crate::algorithm::{factorial, permutation_swaps};
 
 pub const ROBOT_COUNT: usize = 4;
 pub const BOARD_SIZE: usize = 16;
 pub const WALL_MAP_SIZE: usize = BOARD_SIZE * 2 + 1;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Point {
     pub r: i8,
     pub c: i8,
@@ -64,7 +67,7 @@ impl ops::Mul<i8> for Point {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Direction {
     Up = 0,
     Down = 1,
@@ -80,7 +83,7 @@ pub const DIRECTIONS: [Direction; 4] = [
 ];
 
 impl Direction {
-    fn reverse(&self) -> Direction {
+    pub fn reverse(&self) -> Direction {
         match &self {
             Direction::Up => Direction::Down,
             Direction::Down => Direction::Up,
@@ -116,7 +119,7 @@ impl TargetType {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct GameMove {
     pub robot_index: u8,
     pub direction: Direction,
@@ -129,7 +132,9 @@ pub static GAME_MOVES: LazyLock<[GameMove; ROBOT_COUNT * 4]> = LazyLock::new(||
     })
 });
 
-type WallBoard = [[bool; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+pub type WallBoard = [[bool; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+// moves needed per cell; None if the cell can't be reached.
+pub type DistanceGrid = [[Option<u8>; BOARD_SIZE]; BOARD_SIZE];
 type WallCache = [[[u8; 4]; BOARD_SIZE]; BOARD_SIZE];
 
 static THREE_PERMUTATION_SWAPS: LazyLock<[usize; 5]> = LazyLock::new(|| {
@@ -141,12 +146,24 @@ static FOUR_PERMUTATION_SWAPS: LazyLock<[usize; 23]> = LazyLock::new(|| {
     array::from_fn(|i| *v.get(i).unwrap())
 });
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Goal {
     pub position: Point,
     pub target_type: TargetType,
 }
 
+impl Goal {
+    pub fn is_reached(&self, state: &GameState) -> bool {
+        match self.target_type {
+            TargetType::Any => state
+                .robots
+                .iter()
+                .any(|position| position == &self.position),
+            TargetType::Particular(robot_index) => state.robots[robot_index] == self.position,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct GameSpec {
     pub walls: WallBoard,
@@ -160,7 +177,26 @@ fn _has_wall(walls: &WallBoard, position: Point, direction: Direction) -> bool {
     walls[wall_position.r as usize][wall_position.c as usize]
 }
 
+// moves only stop at the edge of the board if every border cell has its outer wall.
+pub fn has_border_walls(walls: &WallBoard) -> bool {
+    (0..BOARD_SIZE).all(|i| {
+        let cell = i * 2 + 1;
+        walls[0][cell]
+            && walls[WALL_MAP_SIZE - 1][cell]
+            && walls[cell][0]
+            && walls[cell][WALL_MAP_SIZE - 1]
+    })
+}
+
 impl GameSpec {
+    // like new, but refuses boards a robot could slide off of.
+    pub fn try_new(walls: WallBoard, goals: Vec<Goal>) -> Option<GameSpec> {
+        if !has_border_walls(&walls) {
+            return None;
+        }
+        Some(GameSpec::new(walls, goals))
+    }
+
     pub fn new(walls: WallBoard, goals: Vec<Goal>) -> GameSpec {
         let mut wall_cache = [[[0; 4]; BOARD_SIZE]; BOARD_SIZE];
         wall_cache.iter_mut().enumerate().for_each(|(r, row)| {
@@ -188,6 +224,10 @@ impl GameSpec {
         }
     }
 
+    pub fn has_wall(&self, position: Point, direction: Direction) -> bool {
+        _has_wall(&self.walls, position, direction)
+    }
+
     pub fn prev_states(&self, current_state: &GameState) -> Vec<GameState> {
         let mut results = Vec::new();
         for robot_index in 0..ROBOT_COUNT {
@@ -240,16 +280,68 @@ impl GameSpec {
         results
     }
 
-    pub fn is_winning_state(&self, state: &GameState) -> bool {
-        self.goals.iter().all(|goal| {
-            match goal.target_type {
-                TargetType::Any => state
-                    .robots
-                    .iter()
-                    .any(|position| position == &goal.position),
-                TargetType::Particular(robot_index) => state.robots[robot_index] == goal.position,
+    pub fn apply_move(&self, current_state: &GameState, game_move: &GameMove) -> GameState {
+        let index = game_move.robot_index as usize * 4 + game_move.direction as usize;
+        self.next_states(current_state)[index].clone()
+    }
+
+    // moves the robot needs to reach each cell while the other robots stay put.
+    pub fn robot_distances(&self, state: &GameState, robot_index: usize) -> DistanceGrid {
+        let mut distances: DistanceGrid = [[None; BOARD_SIZE]; BOARD_SIZE];
+        let start = state.robots[robot_index];
+        distances[start.r as usize][start.c as usize] = Some(0);
+        let mut q = VecDeque::new();
+        q.push_back(state.clone());
+        while let Some(current_state) = q.pop_front() {
+            let position = current_state.robots[robot_index];
+            let distance = distances[position.r as usize][position.c as usize].unwrap();
+            let next_states = self.next_states(&current_state);
+            for next_state in &next_states[robot_index * 4..robot_index * 4 + 4] {
+                let next = next_state.robots[robot_index];
+                let cell = &mut distances[next.r as usize][next.c as usize];
+                if cell.is_none() {
+                    *cell = Some(distance + 1);
+                    q.push_back(next_state.clone());
+                }
             }
-        })
+        }
+        distances
+    }
+
+    // moves needed to bring the robot to each cell when every robot may move,
+    // searching up to `max_moves` moves.
+    pub fn robot_distances_with_moving_robots(
+        &self,
+        state: &GameState,
+        robot_index: usize,
+        max_moves: u8,
+    ) -> DistanceGrid {
+        let mut distances: DistanceGrid = [[None; BOARD_SIZE]; BOARD_SIZE];
+        let mut vis = HashSet::new();
+        vis.insert(state.to_u32());
+        let mut layer = vec![state.clone()];
+        for distance in 0..=max_moves {
+            for current_state in &layer {
+                let position = current_state.robots[robot_index];
+                let cell = &mut distances[position.r as usize][position.c as usize];
+                if cell.is_none() {
+                    *cell = Some(distance);
+                }
+            }
+            if distance == max_moves {
+                break;
+            }
+            layer = layer
+                .iter()
+                .flat_map(|current_state| self.next_states(current_state))
+                .filter(|next_state| vis.insert(next_state.to_u32()))
+                .collect();
+        }
+        distances
+    }
+
+    pub fn is_winning_state(&self, state: &GameState) -> bool {
+        self.goals.iter().all(|goal| goal.is_reached(state))
     }
 
     pub fn equivalent_states_any(&self, state: &GameState) -> [GameState; factorial(ROBOT_COUNT)] {
@@ -350,3 +442,132 @@ impl GameState {
             .any(|robot_position| robot_position == position)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::ID;
+    use crate::serialize;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_try_new() {
+        let (spec, _) = serialize::load(ID);
+        assert!(GameSpec::try_new(spec.walls, spec.goals.clone()).is_some());
+        let mut walls = spec.walls;
+        walls[0][1] = false;
+        assert!(GameSpec::try_new(walls, spec.goals).is_none());
+    }
+
+    #[test]
+    fn test_robot_distances() {
+        let (spec, state) = serialize::load(ID);
+        let distances = spec.robot_distances(&state, 0);
+        let start = state.robots[0];
+        assert_eq!(distances[start.r as usize][start.c as usize], Some(0));
+        for next_state in &spec.next_states(&state)[0..4] {
+            let next = next_state.robots[0];
+            if next != start {
+                assert_eq!(distances[next.r as usize][next.c as usize], Some(1));
+            }
+        }
+
+        // letting the other robots move never makes a cell farther.
+        let moving = spec.robot_distances_with_moving_robots(&state, 0, 3);
+        for r in 0..BOARD_SIZE {
+            for c in 0..BOARD_SIZE {
+                if let Some(distance) = distances[r][c].filter(|distance| *distance <= 3) {
+                    assert!(moving[r][c].unwrap() <= distance);
+                }
+            }
+        }
+    }
+
+    // the move rules spelled out: step cell by cell until a wall or a robot.
+    fn simulate_move(
+        spec: &GameSpec,
+        state: &GameState,
+        robot_index: usize,
+        direction: Direction,
+    ) -> GameState {
+        let mut next_state = state.clone();
+        loop {
+            let position = next_state.robots[robot_index];
+            let next_position = position + Point::from(direction);
+            if spec.has_wall(position, direction) || state.robots.contains(&next_position) {
+                return next_state;
+            }
+            next_state.robots[robot_index] = next_position;
+        }
+    }
+
+    // the border walls, and every wall between two cells with probability 1/8.
+    // the wall map entries between cells are shared by both, so any choice is
+    // a consistent board.
+    fn arb_spec() -> impl Strategy<Value = GameSpec> {
+        prop::collection::vec(prop::bool::weighted(0.125), WALL_MAP_SIZE * WALL_MAP_SIZE).prop_map(
+            |walls| {
+                let mut wall_map = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+                for (r, row) in wall_map.iter_mut().enumerate() {
+                    for (c, wall) in row.iter_mut().enumerate() {
+                        let border =
+                            r == 0 || c == 0 || r == WALL_MAP_SIZE - 1 || c == WALL_MAP_SIZE - 1;
+                        *wall = border || walls[r * WALL_MAP_SIZE + c];
+                    }
+                }
+                let goal = Goal {
+                    position: Point::new(0, 0),
+                    target_type: TargetType::Any,
+                };
+                GameSpec::new(wall_map, vec![goal])
+            },
+        )
+    }
+
+    fn arb_state() -> impl Strategy<Value = GameState> {
+        let cells: Vec<usize> = (0..BOARD_SIZE * BOARD_SIZE).collect();
+        prop::sample::subsequence(cells, ROBOT_COUNT)
+            .prop_shuffle()
+            .prop_map(|cells| GameState {
+                robots: array::from_fn(|i| {
+                    Point::new((cells[i] / BOARD_SIZE) as i8, (cells[i] % BOARD_SIZE) as i8)
+                }),
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_next_states_match_simulation(spec in arb_spec(), state in arb_state()) {
+            let next_states = spec.next_states(&state);
+            for robot_index in 0..ROBOT_COUNT {
+                for direction in DIRECTIONS {
+                    prop_assert_eq!(
+                        &next_states[robot_index * 4 + direction as usize],
+                        &simulate_move(&spec, &state, robot_index, direction),
+                        "robot {} moving {:?}", robot_index, direction
+                    );
+                }
+            }
+        }
+
+        // every predecessor reaches the state in one move, and every state one
+        // move away lists it as a predecessor.
+        #[test]
+        fn test_prev_states_invert_next_states(spec in arb_spec(), state in arb_state()) {
+            for prev_state in spec.prev_states(&state) {
+                prop_assert!(
+                    spec.next_states(&prev_state).contains(&state),
+                    "{:?} can't reach {:?}", prev_state, state
+                );
+            }
+            for next_state in spec.next_states(&state) {
+                if next_state != state {
+                    prop_assert!(
+                        spec.prev_states(&next_state).contains(&state),
+                        "{:?} isn't a predecessor of {:?}", state, next_state
+                    );
+                }
+            }
+        }
+    }
+}
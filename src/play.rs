@@ -0,0 +1,163 @@
+// a single-player session for the terminal game: moves with undo and redo,
+// and a fresh start for replaying a revealed solution.
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::analysis;
+use crate::model::{Direction, GameMove, GameSpec, GameState, Point, BOARD_SIZE};
+
+pub struct Session {
+    pub spec: GameSpec,
+    pub initial_state: GameState,
+    pub selected: usize,
+    // moves so far, with the state after each
+    history: Vec<(GameMove, GameState)>,
+    // undone moves; the next one to redo is last
+    undone: Vec<GameMove>,
+}
+
+impl Session {
+    pub fn new(spec: GameSpec, initial_state: GameState) -> Session {
+        Session {
+            spec,
+            initial_state,
+            selected: 0,
+            history: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        self.history
+            .last()
+            .map_or(&self.initial_state, |(_, state)| state)
+    }
+
+    pub fn moves(&self) -> Vec<GameMove> {
+        self.history
+            .iter()
+            .map(|(game_move, _)| game_move.clone())
+            .collect()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.spec.is_winning_state(self.state())
+    }
+
+    // moves the selected robot; false if it can't go that way.
+    pub fn play(&mut self, direction: Direction) -> bool {
+        let game_move = GameMove {
+            robot_index: self.selected as u8,
+            direction,
+        };
+        if !self.push(game_move) {
+            return false;
+        }
+        self.undone.clear();
+        true
+    }
+
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some((game_move, _)) => {
+                self.undone.push(game_move);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(game_move) => self.push(game_move),
+            None => false,
+        }
+    }
+
+    // back to the initial state, as if nothing had been played.
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.undone.clear();
+    }
+
+    // plays any robot's move, as the reveal animation does.
+    pub fn push(&mut self, game_move: GameMove) -> bool {
+        let state = self.spec.apply_move(self.state(), &game_move);
+        if state == *self.state() {
+            return false;
+        }
+        self.history.push((game_move, state));
+        true
+    }
+}
+
+// robots on distinct cells where a robot can stop, away from the goals.
+pub fn random_state(spec: &GameSpec, seed: u64) -> GameState {
+    let dead = analysis::dead_cells(spec);
+    let mut cells: Vec<Point> = (0..BOARD_SIZE)
+        .flat_map(|r| (0..BOARD_SIZE).map(move |c| Point::new(r as i8, c as i8)))
+        .filter(|cell| !dead[cell.r as usize][cell.c as usize])
+        .filter(|cell| spec.goals.iter().all(|goal| goal.position != *cell))
+        .collect();
+    cells.shuffle(&mut StdRng::seed_from_u64(seed));
+    GameState {
+        robots: std::array::from_fn(|i| cells[i]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::ID;
+    use crate::model::DIRECTIONS;
+    use crate::serialize;
+
+    #[test]
+    fn test_undo_redo() {
+        let (spec, state) = serialize::load(ID);
+        let mut session = Session::new(spec, state.clone());
+        let directions: Vec<Direction> = DIRECTIONS
+            .into_iter()
+            .filter(|&direction| {
+                session.spec.apply_move(
+                    &state,
+                    &GameMove {
+                        robot_index: 0,
+                        direction,
+                    },
+                ) != state
+            })
+            .collect();
+
+        assert!(session.play(directions[0]));
+        let after_first = session.state().clone();
+        assert!(session.undo());
+        assert_eq!(*session.state(), state);
+        assert!(session.redo());
+        assert_eq!(*session.state(), after_first);
+        assert!(!session.redo());
+
+        // a new move drops what was undone.
+        session.undo();
+        session.play(directions[1]);
+        assert!(!session.redo());
+        assert_eq!(session.moves().len(), 1);
+
+        session.reset();
+        assert_eq!(*session.state(), state);
+        assert!(!session.undo());
+    }
+
+    #[test]
+    fn test_random_state() {
+        let (spec, _) = serialize::load(ID);
+        let state = random_state(&spec, 3);
+        assert_eq!(state, random_state(&spec, 3));
+        let dead = analysis::dead_cells(&spec);
+        for (i, robot) in state.robots.iter().enumerate() {
+            assert!(!state.robots[..i].contains(robot));
+            assert!(!dead[robot.r as usize][robot.c as usize]);
+            assert_ne!(*robot, spec.goals[0].position);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use crate::model::{
+    DistanceGrid, GameSpec, GameState, Point, TargetType, BOARD_SIZE, WALL_MAP_SIZE,
+};
+
+const ROBOT_LETTERS: [char; 4] = ['R', 'B', 'G', 'Y'];
+const ROBOT_COLORS: [&str; 4] = ["#e53935", "#1e88e5", "#43a047", "#fdd835"];
+const WILD_COLOR: &str = "#8e24aa";
+const CELL: usize = 32;
+
+fn robot_at(state: &GameState, position: Point) -> Option<usize> {
+    state.robots.iter().position(|robot| *robot == position)
+}
+
+fn goal_at(spec: &GameSpec, position: Point) -> Option<TargetType> {
+    spec.goals
+        .iter()
+        .find(|goal| goal.position == position)
+        .map(|goal| goal.target_type)
+}
+
+// one character row per wall row and cell row. cells show a robot letter,
+// else the overlay distance, else `*` for a goal.
+pub fn ascii(spec: &GameSpec, state: &GameState, overlay: Option<&DistanceGrid>) -> String {
+    let mut text = String::new();
+    for wall_r in 0..WALL_MAP_SIZE {
+        for wall_c in 0..WALL_MAP_SIZE {
+            let wall = spec.walls[wall_r][wall_c];
+            match (wall_r % 2, wall_c % 2) {
+                (0, 0) => text.push('+'),
+                (0, _) => text.push_str(if wall { "---" } else { "   " }),
+                (_, 0) => text.push(if wall { '|' } else { ' ' }),
+                _ => {
+                    let position = Point::new((wall_r / 2) as i8, (wall_c / 2) as i8);
+                    let distance = overlay.and_then(|grid| grid[wall_r / 2][wall_c / 2]);
+                    let cell = match (robot_at(state, position), distance) {
+                        (Some(robot_index), _) => ROBOT_LETTERS[robot_index].to_string(),
+                        (None, Some(distance)) => distance.to_string(),
+                        (None, None) if goal_at(spec, position).is_some() => "*".to_string(),
+                        (None, None) => String::new(),
+                    };
+                    write!(text, "{:^3}", cell).unwrap();
+                }
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+pub fn svg(spec: &GameSpec, state: &GameState, overlay: Option<&DistanceGrid>) -> String {
+    let size = CELL * BOARD_SIZE;
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="-2 -2 {1} {1}">"#,
+        size,
+        size + 4
+    )
+    .unwrap();
+    writeln!(svg, r##"<rect x="0" y="0" width="{0}" height="{0}" fill="#f5f5f5"/>"##, size).unwrap();
+
+    let max_distance = overlay
+        .map(|grid| grid.iter().flatten().flatten().copied().max().unwrap_or(0).max(1))
+        .unwrap_or(1);
+    for r in 0..BOARD_SIZE {
+        for c in 0..BOARD_SIZE {
+            let (x, y) = (c * CELL, r * CELL);
+            writeln!(
+                svg,
+                r##"<rect x="{}" y="{}" width="{2}" height="{2}" fill="none" stroke="#ddd"/>"##,
+                x, y, CELL
+            )
+            .unwrap();
+            if let Some(distance) = overlay.and_then(|grid| grid[r][c]) {
+                // green when close, red when far
+                let hue = 120 - 120 * distance as usize / max_distance as usize;
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="hsl({3}, 70%, 60%)" fill-opacity="0.6"/>"#,
+                    x, y, CELL, hue
+                )
+                .unwrap();
+                writeln!(
+                    svg,
+                    r#"<text x="{}" y="{}" font-size="11" font-family="monospace">{}</text>"#,
+                    x + 2,
+                    y + 11,
+                    distance
+                )
+                .unwrap();
+            }
+            if let Some(target_type) = goal_at(spec, Point::new(r as i8, c as i8)) {
+                let color = match target_type {
+                    TargetType::Any => WILD_COLOR,
+                    TargetType::Particular(robot_index) => ROBOT_COLORS[robot_index],
+                };
+                writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{2}" height="{2}" fill="{3}" fill-opacity="0.5"/>"#,
+                    x + CELL / 4,
+                    y + CELL / 4,
+                    CELL / 2,
+                    color
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    for (robot_index, robot) in state.robots.iter().enumerate() {
+        writeln!(
+            svg,
+            r##"<circle cx="{}" cy="{}" r="{}" fill="{}" stroke="#333"/>"##,
+            robot.c as usize * CELL + CELL / 2,
+            robot.r as usize * CELL + CELL / 2,
+            CELL * 3 / 8,
+            ROBOT_COLORS[robot_index]
+        )
+        .unwrap();
+    }
+
+    // walls sit on the odd/even positions of the wall map between cells.
+    for wall_r in 0..WALL_MAP_SIZE {
+        for wall_c in 0..WALL_MAP_SIZE {
+            if !spec.walls[wall_r][wall_c] || (wall_r % 2 == 0) == (wall_c % 2 == 0) {
+                continue;
+            }
+            let (x1, y1, x2, y2) = if wall_r % 2 == 0 {
+                let y = wall_r / 2 * CELL;
+                (wall_c / 2 * CELL, y, (wall_c / 2 + 1) * CELL, y)
+            } else {
+                let x = wall_c / 2 * CELL;
+                (x, wall_r / 2 * CELL, x, (wall_r / 2 + 1) * CELL)
+            };
+            writeln!(
+                svg,
+                r##"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#222" stroke-width="4" stroke-linecap="square"/>"##,
+                x1, y1, x2, y2
+            )
+            .unwrap();
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::ID;
+    use crate::serialize;
+
+    #[test]
+    fn test_ascii_overlay() {
+        let (spec, state) = serialize::load(ID);
+        let distances = spec.robot_distances(&state, 0);
+        let text = ascii(&spec, &state, Some(&distances));
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), WALL_MAP_SIZE);
+        assert!(lines.iter().all(|line| line.len() == BOARD_SIZE * 4 + 1));
+        assert!(lines[0].starts_with("+---+---+"));
+
+        let robot = state.robots[0];
+        let row = lines[robot.r as usize * 2 + 1];
+        assert_eq!(&row[robot.c as usize * 4 + 1..robot.c as usize * 4 + 4], " R ");
+    }
+}
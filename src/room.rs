@@ -0,0 +1,535 @@
+// game rooms over websocket. every connection has a thread that reads its
+// player's json messages and writes the room's broadcasts back. a ticker thread
+// runs the bidding timers, and one solver thread reveals the optimal answer
+// after each round.
+
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+
+use crate::bidding::{BiddingRound, BiddingRules, Event, Outcome};
+use crate::game::Game;
+use crate::json::{self, Target, Wall};
+use crate::model::{GameMove, GameSpec, GameState, Goal, Point, ROBOT_COUNT};
+use crate::serialize;
+use crate::solver::Solver;
+
+// how long a connection waits for its player before checking for broadcasts
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone)]
+pub struct RoomConfig {
+    // kaseken id every new room starts from
+    pub board: String,
+    // shuffles the chips of new rooms
+    pub seed: Option<u64>,
+    pub rules: BiddingRules,
+    // real time per second of the bidding timer
+    pub tick: Duration,
+}
+
+impl RoomConfig {
+    pub fn new(board: &str) -> RoomConfig {
+        RoomConfig {
+            board: board.to_string(),
+            seed: None,
+            rules: BiddingRules::default(),
+            tick: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join { room: String, name: String },
+    // draw the next chip; only between rounds
+    Draw,
+    Bid { moves: usize },
+    Solution { moves: Vec<GameMove> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Welcome {
+        player: usize,
+        players: Vec<String>,
+        scores: Vec<usize>,
+        id: String,
+        walls: Vec<Wall>,
+        robots: [Point; ROBOT_COUNT],
+    },
+    PlayerJoined {
+        player: usize,
+        name: String,
+    },
+    ChipDrawn {
+        goal: Target,
+        robots: [Point; ROBOT_COUNT],
+        remaining: usize,
+    },
+    Bidding {
+        outcome: Outcome,
+    },
+    RoundOver {
+        winner: Option<usize>,
+        scores: Vec<usize>,
+        robots: [Point; ROBOT_COUNT],
+    },
+    // the shortest solution from where the round started; None if there is none
+    Optimal {
+        moves: Option<Vec<GameMove>>,
+    },
+    GameOver {
+        scores: Vec<usize>,
+    },
+    Error {
+        message: String,
+    },
+}
+
+struct Room {
+    game: Game,
+    names: Vec<String>,
+    // outgoing messages per player; None once the player disconnected
+    clients: Vec<Option<Sender<String>>>,
+    round: Option<BiddingRound>,
+    // the robots when the current chip was drawn
+    round_start: Option<GameState>,
+}
+
+impl Room {
+    fn send(&self, player: usize, message: &ServerMessage) {
+        if let Some(client) = &self.clients[player] {
+            let _ = client.send(serde_json::to_string(message).unwrap());
+        }
+    }
+
+    fn broadcast(&self, message: &ServerMessage) {
+        for player in 0..self.clients.len() {
+            self.send(player, message);
+        }
+    }
+}
+
+// a finished round to reveal the optimal answer for
+struct SolveJob {
+    room: String,
+    spec: GameSpec,
+    state: GameState,
+}
+
+struct Shared {
+    config: RoomConfig,
+    rooms: Mutex<HashMap<String, Room>>,
+    solve_jobs: Mutex<Sender<SolveJob>>,
+    stopping: AtomicBool,
+}
+
+impl Shared {
+    // a panic while a room is locked must not take the other rooms down with it
+    fn rooms(&self) -> MutexGuard<'_, HashMap<String, Room>> {
+        self.rooms.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn solve_jobs(&self) -> MutexGuard<'_, Sender<SolveJob>> {
+        self.solve_jobs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+pub struct RoomServer {
+    shared: Arc<Shared>,
+    addr: SocketAddr,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl RoomServer {
+    pub fn start(addr: impl ToSocketAddrs, config: RoomConfig) -> io::Result<RoomServer> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let (solve_jobs, solve_queue) = mpsc::channel();
+        let shared = Arc::new(Shared {
+            config,
+            rooms: Mutex::new(HashMap::new()),
+            solve_jobs: Mutex::new(solve_jobs),
+            stopping: AtomicBool::new(false),
+        });
+
+        let threads = vec![
+            {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || accept(listener, &shared))
+            },
+            {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || tick(&shared))
+            },
+            {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || reveal(solve_queue, &shared))
+            },
+        ];
+        Ok(RoomServer {
+            shared,
+            addr,
+            threads,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn wait(self) {
+        for thread in self.threads {
+            thread.join().unwrap();
+        }
+    }
+
+    // closes every connection and stops the background threads.
+    pub fn shutdown(self) {
+        self.shared.stopping.store(true, Ordering::SeqCst);
+        // wake the accept loop up
+        let _ = TcpStream::connect(self.addr);
+        // close the solve queue by swapping in a sender nobody reads from
+        drop(std::mem::replace(
+            &mut *self.shared.solve_jobs(),
+            mpsc::channel().0,
+        ));
+        self.wait();
+    }
+}
+
+fn accept(listener: TcpListener, shared: &Arc<Shared>) {
+    let mut connections = Vec::new();
+    for stream in listener.incoming() {
+        if shared.stopping.load(Ordering::SeqCst) {
+            break;
+        }
+        let Ok(stream) = stream else { continue };
+        let shared = Arc::clone(shared);
+        connections.push(thread::spawn(move || connect(stream, &shared)));
+    }
+    for connection in connections {
+        connection.join().unwrap();
+    }
+}
+
+fn tick(shared: &Shared) {
+    while !shared.stopping.load(Ordering::SeqCst) {
+        thread::sleep(shared.config.tick);
+        let mut rooms = shared.rooms();
+        for (name, room) in rooms.iter_mut() {
+            if room.round.is_some() {
+                let _ = play(shared, name, room, Event::Tick { seconds: 1 });
+            }
+        }
+    }
+}
+
+fn reveal(solve_queue: Receiver<SolveJob>, shared: &Shared) {
+    // allocated for the first finished round
+    let mut solver: Option<Solver> = None;
+    for job in solve_queue {
+        let moves = solver
+            .get_or_insert_with(Solver::new)
+            .solve(&job.spec, &job.state, None)
+            .ok()
+            .map(|solution| solution.moves);
+        if let Some(room) = shared.rooms().get(&job.room) {
+            room.broadcast(&ServerMessage::Optimal { moves });
+        }
+    }
+}
+
+fn connect(stream: TcpStream, shared: &Shared) {
+    let Ok(mut socket) = tungstenite::accept(stream) else {
+        return;
+    };
+    socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .unwrap();
+    let (outgoing, messages) = mpsc::channel();
+    // (room, player) once joined
+    let mut seat: Option<(String, usize)> = None;
+
+    while !shared.stopping.load(Ordering::SeqCst) {
+        if !flush(&mut socket, &messages) {
+            break;
+        }
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(error))
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) =>
+            {
+                continue
+            }
+            Err(_) => break,
+        };
+        let reply = match serde_json::from_str(&text) {
+            Ok(message) => handle(shared, &mut seat, &outgoing, message).err(),
+            Err(error) => Some(error.to_string()),
+        };
+        if let Some(message) = reply {
+            let error = ServerMessage::Error { message };
+            let _ = outgoing.send(serde_json::to_string(&error).unwrap());
+        }
+    }
+
+    flush(&mut socket, &messages);
+    let _ = socket.close(None);
+    if let Some((name, player)) = seat {
+        if let Some(room) = shared.rooms().get_mut(&name) {
+            room.clients[player] = None;
+        }
+    }
+}
+
+// writes the pending broadcasts; false once the connection is gone.
+fn flush(socket: &mut WebSocket<TcpStream>, messages: &Receiver<String>) -> bool {
+    messages
+        .try_iter()
+        .all(|text| socket.send(Message::text(text)).is_ok())
+}
+
+fn handle(
+    shared: &Shared,
+    seat: &mut Option<(String, usize)>,
+    outgoing: &Sender<String>,
+    message: ClientMessage,
+) -> Result<(), String> {
+    let mut rooms = shared.rooms();
+    if let ClientMessage::Join { room, name } = message {
+        if seat.is_some() {
+            return Err("already joined".to_string());
+        }
+        let config = &shared.config;
+        let room_state = rooms.entry(room.clone()).or_insert_with(|| Room {
+            game: Game::from_id(&config.board, 0, config.seed),
+            names: Vec::new(),
+            clients: Vec::new(),
+            round: None,
+            round_start: None,
+        });
+        let player = room_state.game.add_player();
+        room_state.names.push(name.clone());
+        room_state.clients.push(Some(outgoing.clone()));
+        *seat = Some((room, player));
+
+        let game = &room_state.game;
+        room_state.send(
+            player,
+            &ServerMessage::Welcome {
+                player,
+                players: room_state.names.clone(),
+                scores: game.scores(),
+                // the board as configured, with the robots where they are now
+                id: serialize::unify_ids(&config.board, &serialize::dump(&game.spec, &game.state)),
+                walls: json::walls(&game.spec),
+                robots: game.state.robots,
+            },
+        );
+        if let Some(goal) = game.current_target() {
+            room_state.send(player, &chip_drawn(game, goal));
+        }
+        room_state.broadcast(&ServerMessage::PlayerJoined { player, name });
+        return Ok(());
+    }
+
+    let Some((name, player)) = seat.as_ref() else {
+        return Err("join a room first".to_string());
+    };
+    let player = *player;
+    let room = rooms.get_mut(name).unwrap();
+    match message {
+        ClientMessage::Join { .. } => unreachable!(),
+        ClientMessage::Draw => {
+            if room.round.is_some() {
+                return Err("a round is in progress".to_string());
+            }
+            let Some(goal) = room.game.draw().cloned() else {
+                return Err("no chips left".to_string());
+            };
+            room.round = Some(BiddingRound::new(shared.config.rules.clone()));
+            room.round_start = Some(room.game.state.clone());
+            room.broadcast(&chip_drawn(&room.game, &goal));
+            Ok(())
+        }
+        ClientMessage::Bid { moves } => play(shared, name, room, Event::Bid { player, moves }),
+        ClientMessage::Solution { moves } => {
+            if let Some(game_move) = moves
+                .iter()
+                .find(|game_move| game_move.robot_index as usize >= ROBOT_COUNT)
+            {
+                return Err(format!("unknown robot {}", game_move.robot_index));
+            }
+            play(shared, name, room, Event::Demonstrate { player, moves })
+        }
+    }
+}
+
+fn chip_drawn(game: &Game, goal: &Goal) -> ServerMessage {
+    ServerMessage::ChipDrawn {
+        goal: Target::from(goal),
+        robots: game.state.robots,
+        remaining: game.remaining_chips(),
+    }
+}
+
+// feeds the event to the room's bidding round and broadcasts what happened.
+fn play(shared: &Shared, name: &str, room: &mut Room, event: Event) -> Result<(), String> {
+    let Some(round) = room.round.as_mut() else {
+        return Err("no round in progress".to_string());
+    };
+    // the round spec has to be taken before a winning demonstration moves the robots
+    let spec = room.game.round_spec();
+    let outcomes = round
+        .handle(&mut room.game, event)
+        .map_err(|error| error.to_string())?;
+    let over = round.is_over();
+    for outcome in &outcomes {
+        room.broadcast(&ServerMessage::Bidding {
+            outcome: outcome.clone(),
+        });
+    }
+    if !over {
+        return Ok(());
+    }
+
+    room.round = None;
+    let winner = outcomes.iter().find_map(|outcome| match outcome {
+        Outcome::RoundWon { player, .. } => Some(*player),
+        _ => None,
+    });
+    room.broadcast(&ServerMessage::RoundOver {
+        winner,
+        scores: room.game.scores(),
+        robots: room.game.state.robots,
+    });
+    if room.game.is_over() {
+        room.broadcast(&ServerMessage::GameOver {
+            scores: room.game.scores(),
+        });
+    }
+    let _ = shared.solve_jobs().send(SolveJob {
+        room: name.to_string(),
+        spec: spec.unwrap(),
+        state: room.round_start.take().unwrap(),
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{near_goal_id, ID};
+    use serde_json::{json, Value};
+    use tungstenite::stream::MaybeTlsStream;
+
+    use crate::solver;
+
+    type Client = WebSocket<MaybeTlsStream<TcpStream>>;
+
+    fn join(addr: SocketAddr, name: &str) -> Client {
+        let (mut client, _) = tungstenite::connect(format!("ws://{}/", addr)).unwrap();
+        if let MaybeTlsStream::Plain(stream) = client.get_ref() {
+            stream
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+        }
+        send(
+            &mut client,
+            json!({ "type": "join", "room": "table", "name": name }),
+        );
+        client
+    }
+
+    fn send(client: &mut Client, message: Value) {
+        client.send(Message::text(message.to_string())).unwrap();
+    }
+
+    // skips messages until one of the given type arrives.
+    fn expect(client: &mut Client, kind: &str) -> Value {
+        loop {
+            let text = client.read().unwrap().into_text().unwrap();
+            let message: Value = serde_json::from_str(&text).unwrap();
+            if message["type"] == kind {
+                return message;
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_over_websocket() {
+        let id = near_goal_id(&serialize::load_board_goals(ID)[0]);
+        // solved up front: a tick is a second here, so the demonstration is due soon
+        let game = Game::from_id(&id, 0, None);
+        let moves = solver::solve_bfs(
+            &GameSpec::new(game.spec.walls, vec![game.spec.goals[0].clone()]),
+            &game.state,
+        )
+        .unwrap();
+        let mut config = RoomConfig::new(&id);
+        config.rules.timer_seconds = 2;
+        config.tick = Duration::from_millis(10);
+        let server = RoomServer::start("127.0.0.1:0", config).unwrap();
+
+        let mut alice = join(server.addr(), "alice");
+        assert_eq!(expect(&mut alice, "welcome")["player"], 0);
+        let mut bob = join(server.addr(), "bob");
+        let welcome = expect(&mut bob, "welcome");
+        assert_eq!(welcome["player"], 1);
+        assert_eq!(welcome["players"], json!(["alice", "bob"]));
+        // everyone hears about every join, their own included.
+        assert_eq!(expect(&mut alice, "player_joined")["name"], "alice");
+        assert_eq!(expect(&mut alice, "player_joined")["name"], "bob");
+
+        // bids before a chip is drawn are refused.
+        send(&mut bob, json!({ "type": "bid", "moves": 3 }));
+        assert_eq!(expect(&mut bob, "error")["message"], "no round in progress");
+
+        send(&mut alice, json!({ "type": "draw" }));
+        expect(&mut alice, "chip_drawn");
+        expect(&mut bob, "chip_drawn");
+        send(&mut bob, json!({ "type": "bid", "moves": 3 }));
+        send(&mut alice, json!({ "type": "bid", "moves": 1 }));
+        let closed = loop {
+            let message = expect(&mut bob, "bidding");
+            if message["outcome"]["event"] == "bidding_closed" {
+                break message;
+            }
+        };
+        assert_eq!(closed["outcome"]["order"], json!([0, 1]));
+
+        // a robot that doesn't exist is refused without hurting the room
+        let unknown_robot = json!([{ "robot_index": 7, "direction": "Up" }]);
+        send(
+            &mut alice,
+            json!({ "type": "solution", "moves": unknown_robot }),
+        );
+        assert_eq!(expect(&mut alice, "error")["message"], "unknown robot 7");
+        send(&mut alice, json!({ "type": "solution", "moves": moves }));
+        let round_over = expect(&mut bob, "round_over");
+        assert_eq!(round_over["winner"], 0);
+        assert_eq!(round_over["scores"], json!([1, 0]));
+        assert_eq!(expect(&mut bob, "optimal")["moves"], json!(moves));
+
+        drop(alice);
+        drop(bob);
+        server.shutdown();
+    }
+}
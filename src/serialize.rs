@@ -3,9 +3,12 @@
 use std::array::from_fn;
 
 use crate::model::{
-    GameSpec, GameState, Goal, Point, TargetType, BOARD_SIZE, ROBOT_COUNT, WALL_MAP_SIZE,
+    has_border_walls, GameMove, GameSpec, GameState, Goal, Point, TargetType, WallBoard, BOARD_SIZE, ROBOT_COUNT,
+    WALL_MAP_SIZE,
 };
 
+const URL_PREFIX: &str = "https://kaseken.github.io/ricochet_robots/#/?id=";
+
 const POSITION_LENGTH: usize = 2;
 
 const BASE_ID_START: usize = 0;
@@ -21,6 +24,28 @@ const SINGLE_GOAL_ID_LENGTH: usize = POSITION_LENGTH;
 const ID_LENGTH: usize =
     BASE_ID_LENGTH + NORMAL_GOAL_ID_LENGTH + WILD_GOAL_ID_LENGTH + ROBOT_ID_LENGTH + SINGLE_GOAL_ID_LENGTH;
 
+// true if `load` can read the id without panicking.
+pub fn is_valid_id(base64: &str) -> bool {
+    let bytes = base64.as_bytes();
+    if !bytes.len().is_multiple_of(2)
+        || !bytes
+            .iter()
+            .all(|&code| code.is_ascii_alphanumeric() || code == b'_' || code == b'-')
+    {
+        return false;
+    }
+    let base16 = to_ints(base64);
+    // normal goals are read by shape and robot, so the shape must be one of four
+    base16.len() >= ID_LENGTH
+        && base16[GOAL_ID_START..]
+            .chunks(SINGLE_GOAL_ID_LENGTH)
+            .take(2)
+            .all(|goal| goal.len() < 2 || goal[1] >= ROBOT_COUNT as u8 || goal[0] < 4)
+        && has_border_walls(&read_walls(
+            &base16[BASE_ID_START..(BASE_ID_START + BASE_ID_LENGTH)],
+        ))
+}
+
 pub fn load(base64: &str) -> (GameSpec, GameState) {
     let base16 = to_ints(base64);
     assert!(base16.len() >= ID_LENGTH);
@@ -32,7 +57,7 @@ pub fn load(base64: &str) -> (GameSpec, GameState) {
     let goal_count = ((base16.len() - GOAL_ID_START) / SINGLE_GOAL_ID_LENGTH).min(2);
     let goal_length = goal_count * SINGLE_GOAL_ID_LENGTH;
     let goals = &base16[GOAL_ID_START..GOAL_ID_START + goal_length];
-    assert!(goals.len() % SINGLE_GOAL_ID_LENGTH == 0);
+    assert!(goals.len().is_multiple_of(SINGLE_GOAL_ID_LENGTH));
 
     // robot color order: RBGY
     let goals = goals
@@ -54,33 +79,58 @@ pub fn load(base64: &str) -> (GameSpec, GameState) {
         })
         .collect::<Vec<_>>();
     let robots: [Point; 4] = from_fn(|i| read_point_from_array(robot, i));
-    let mut walls: [[bool; WALL_MAP_SIZE]; WALL_MAP_SIZE] = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
+    let walls = read_walls(base);
+
+    let spec = GameSpec::new(walls, goals);
+    let state = GameState { robots };
+    (spec, state)
+}
 
+// a cell's wall bits are (msb) LDRU (lsb), a clear bit for a wall.
+fn read_walls(base: &[u8]) -> WallBoard {
+    let mut walls: WallBoard = [[false; WALL_MAP_SIZE]; WALL_MAP_SIZE];
     for r in 0..BOARD_SIZE {
         for c in 0..BOARD_SIZE {
             let wall_r = r * 2 + 1;
             let wall_c = c * 2 + 1;
             let idx = r * BOARD_SIZE + c;
             let wall_state = base[idx];
-            // (msb) LDRU (lsb)
-            if wall_state % 2 == 0 {
+            if wall_state.is_multiple_of(2) {
                 walls[wall_r - 1][wall_c] = true;
             }
-            if wall_state / 2 % 2 == 0 {
+            if (wall_state / 2).is_multiple_of(2) {
                 walls[wall_r][wall_c + 1] = true;
             }
-            if wall_state / 4 % 2 == 0 {
+            if (wall_state / 4).is_multiple_of(2) {
                 walls[wall_r + 1][wall_c] = true;
             }
-            if wall_state / 8 % 2 == 0 {
+            if (wall_state / 8).is_multiple_of(2) {
                 walls[wall_r][wall_c - 1] = true;
             }
         }
     }
+    walls
+}
 
-    let spec = GameSpec::new(walls, goals);
-    let state = GameState { robots };
-    (spec, state)
+// every target chip of the board, not just the ones selected in the id.
+// normal goals come in robot color order (RBGY) per shape, the wild goal last.
+pub fn load_board_goals(base64: &str) -> Vec<Goal> {
+    let base16 = to_ints(base64);
+    assert!(base16.len() >= ID_LENGTH);
+
+    let normal_goal = &base16[NORMAL_GOAL_ID_START..(NORMAL_GOAL_ID_START + NORMAL_GOAL_ID_LENGTH)];
+    let wild_goal = &base16[WILD_GOAL_ID_START..(WILD_GOAL_ID_START + WILD_GOAL_ID_LENGTH)];
+    let mut goals: Vec<Goal> = (0..ROBOT_COUNT * 4)
+        .map(|i| Goal {
+            position: read_point_from_array(normal_goal, i),
+            target_type: TargetType::Particular(i % ROBOT_COUNT),
+        })
+        .collect();
+    goals.push(Goal {
+        position: read_point_from_array(wild_goal, 0),
+        target_type: TargetType::Any,
+    });
+    goals
 }
 
 pub fn dump(spec: &GameSpec, state: &GameState) -> String {
@@ -201,7 +251,7 @@ fn int_to_base64char(int: u8) -> char {
 }
 
 fn to_ints(base64: &str) -> Vec<u8> {
-    assert!(base64.len() % 2 == 0);
+    assert!(base64.len().is_multiple_of(2));
 
     let bytes = base64.as_bytes();
     let mut base16: Vec<u8> = vec![];
@@ -217,7 +267,7 @@ fn to_ints(base64: &str) -> Vec<u8> {
 
 fn to_base64(ints: &[u8]) -> String {
     let mut padded = ints.to_vec();
-    while padded.len() % 3 != 0 {
+    while !padded.len().is_multiple_of(3) {
         padded.push(0);
     }
     let ints = &padded;
@@ -252,9 +302,27 @@ pub fn unify_ids(spec_id: &str, state_id: &str) -> String {
     to_base64(&ints)
 }
 
+pub fn to_url(id: &str) -> String {
+    format!("{}{}", URL_PREFIX, id)
+}
+
+// ids of the initial state and of the state after each move, in order.
+pub fn replay_ids(spec: &GameSpec, initial_state: &GameState, moves: &[GameMove]) -> Vec<String> {
+    let spec_id = dump(spec, initial_state);
+    let mut ids = vec![spec_id.clone()];
+    let mut state = initial_state.clone();
+    for game_move in moves {
+        state = spec.apply_move(&state, game_move);
+        ids.push(unify_ids(&spec_id, &dump(spec, &state)));
+    }
+    ids
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::ID;
+    use crate::model::Direction;
 
     #[test]
     fn test_serialize_roundtrip() {
@@ -271,4 +339,49 @@ mod tests {
             dump(&load(id).0, &load(id).1)
         }
     }
+
+    #[test]
+    fn test_is_valid_id() {
+        assert!(is_valid_id(ID));
+        assert!(!is_valid_id(&ID[..ID.len() - 10]));
+        assert!(!is_valid_id(&ID.replace('-', "*")));
+        // opens the top wall of (0, 0)
+        assert!(!is_valid_id(&format!("vK{}", &ID[2..])));
+    }
+
+    #[test]
+    fn test_load_board_goals() {
+        let goals = load_board_goals(ID);
+        assert_eq!(goals.len(), ROBOT_COUNT * 4 + 1);
+        assert_eq!(goals[ROBOT_COUNT * 4].target_type, TargetType::Any);
+        for goal in load(ID).0.goals {
+            assert!(goals.contains(&goal));
+        }
+    }
+
+    #[test]
+    fn test_replay_ids() {
+        let (spec, state) = load(ID);
+        let moves = [
+            GameMove { robot_index: 0, direction: Direction::Up },
+            GameMove { robot_index: 1, direction: Direction::Left },
+            GameMove { robot_index: 0, direction: Direction::Left },
+        ];
+
+        let ids = replay_ids(&spec, &state, &moves);
+        assert_eq!(ids.len(), moves.len() + 1);
+        assert_eq!(ids[0], dump(&spec, &state));
+
+        let expected = [
+            [(8, 14), (14, 5), (13, 11), (10, 8)],
+            [(3, 14), (14, 5), (13, 11), (10, 8)],
+            [(3, 14), (14, 0), (13, 11), (10, 8)],
+            [(3, 11), (14, 0), (13, 11), (10, 8)],
+        ];
+        for (id, robots) in ids.iter().zip(expected) {
+            let (replay_spec, replay_state) = load(id);
+            assert_eq!(replay_spec.goals, spec.goals);
+            assert_eq!(replay_state.robots, robots.map(|(r, c)| Point::new(r, c)));
+        }
+    }
 }
\ No newline at end of file
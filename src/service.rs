@@ -0,0 +1,369 @@
+// a local http service that solves puzzles posted as json. a solve needs the
+// 512MB visited set, so a fixed number of workers keep one solver each and
+// take jobs from a bounded queue; requests that don't fit are turned away.
+//
+// POST /solve with {"id": "<kaseken id>"} or {"puzzle": <json::Puzzle>}.
+
+use std::io::{self, Read};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::json::Puzzle;
+use crate::model::{GameSpec, GameState};
+use crate::serialize;
+use crate::solver::{Solution, SolveError, SolveStats, Solver};
+
+#[derive(Debug, Clone)]
+pub struct ServiceConfig {
+    // solver threads; each one holds 512MB once it has solved something
+    pub workers: usize,
+    // threads reading requests and waiting for their answers
+    pub handlers: usize,
+    // jobs waiting for a worker before requests get 503
+    pub queue_size: usize,
+    // from receiving a request until the answer, queueing included
+    pub timeout: Duration,
+    pub max_body_bytes: u64,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> ServiceConfig {
+        ServiceConfig {
+            workers: 1,
+            handlers: 4,
+            queue_size: 16,
+            timeout: Duration::from_secs(30),
+            max_body_bytes: 64 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SolveRequest {
+    id: Option<String>,
+    puzzle: Option<Puzzle>,
+}
+
+struct Job {
+    spec: GameSpec,
+    state: GameState,
+    queued_at: Instant,
+    deadline: Instant,
+    reply: mpsc::Sender<(Result<Solution, SolveError>, Duration)>,
+}
+
+pub struct Service {
+    server: Arc<Server>,
+    addr: SocketAddr,
+    stopping: Arc<AtomicBool>,
+    handlers: Vec<JoinHandle<()>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Service {
+    // binds the address and starts serving in the background.
+    pub fn start(addr: impl ToSocketAddrs, config: ServiceConfig) -> io::Result<Service> {
+        let server = Server::http(addr).map_err(io::Error::other)?;
+        let addr = server.server_addr().to_ip().unwrap();
+        let server = Arc::new(server);
+        let stopping = Arc::new(AtomicBool::new(false));
+
+        let (jobs, queue) = mpsc::sync_channel(config.queue_size);
+        let queue = Arc::new(Mutex::new(queue));
+        let workers = (0..config.workers)
+            .map(|_| {
+                let queue = Arc::clone(&queue);
+                thread::spawn(move || work(&queue))
+            })
+            .collect();
+        let handlers = (0..config.handlers)
+            .map(|_| {
+                let server = Arc::clone(&server);
+                let stopping = Arc::clone(&stopping);
+                let jobs = jobs.clone();
+                let config = config.clone();
+                thread::spawn(move || loop {
+                    match server.recv() {
+                        // a bug answers one request with a 500 rather than stopping the handler
+                        Ok(request) => {
+                            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                                handle(request, &jobs, &config)
+                            }));
+                        }
+                        Err(_) if stopping.load(Ordering::SeqCst) => break,
+                        Err(_) => {}
+                    }
+                })
+            })
+            .collect();
+
+        Ok(Service {
+            server,
+            addr,
+            stopping,
+            handlers,
+            workers,
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    // blocks until the service is shut down from another thread or the process exits.
+    pub fn wait(self) {
+        for handler in self.handlers {
+            handler.join().unwrap();
+        }
+    }
+
+    // stops taking requests, then waits for the jobs already queued.
+    pub fn shutdown(self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        for _ in 0..self.handlers.len() {
+            self.server.unblock();
+        }
+        for handler in self.handlers {
+            handler.join().unwrap();
+        }
+        // the queue closes once every handler has dropped its sender.
+        for worker in self.workers {
+            worker.join().unwrap();
+        }
+    }
+}
+
+fn work(queue: &Mutex<Receiver<Job>>) {
+    // allocated on the first job so idle workers stay small
+    let mut solver: Option<Solver> = None;
+    loop {
+        // the lock only covers taking the job, so nothing can panic while holding it
+        let next_job = queue.lock().unwrap_or_else(PoisonError::into_inner).recv();
+        let Ok(job) = next_job else {
+            return;
+        };
+        let queued = job.queued_at.elapsed();
+        let result = if Instant::now() >= job.deadline {
+            Err(SolveError::TimedOut(SolveStats {
+                explored_states: 0,
+                elapsed: Duration::ZERO,
+            }))
+        } else {
+            let solve = panic::catch_unwind(AssertUnwindSafe(|| {
+                solver.get_or_insert_with(Solver::new).solve(
+                    &job.spec,
+                    &job.state,
+                    Some(job.deadline),
+                )
+            }));
+            match solve {
+                Ok(result) => result,
+                // the handler answers 500 once `reply` is dropped; the solver's
+                // buffers may be half cleared, so start over with a new one
+                Err(_) => {
+                    solver = None;
+                    continue;
+                }
+            }
+        };
+        // the handler may have given up already
+        let _ = job.reply.send((result, queued));
+    }
+}
+
+fn handle(mut request: Request, jobs: &SyncSender<Job>, config: &ServiceConfig) {
+    let received_at = Instant::now();
+    let (status, body) = if request.url() != "/solve" {
+        (404, json!({ "error": "not found" }))
+    } else if *request.method() != Method::Post {
+        (405, json!({ "error": "use POST" }))
+    } else {
+        let mut text = String::new();
+        let read = request
+            .as_reader()
+            .take(config.max_body_bytes)
+            .read_to_string(&mut text);
+        match read
+            .map_err(|error| error.to_string())
+            .and_then(|_| parse(&text))
+        {
+            Err(error) => (400, json!({ "error": error })),
+            Ok((spec, state)) => solve(spec, state, received_at, jobs, config),
+        }
+    };
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(content_type);
+    // the client may be gone; nothing to do about it
+    let _ = request.respond(response);
+}
+
+fn parse(text: &str) -> Result<(GameSpec, GameState), String> {
+    let request: SolveRequest = serde_json::from_str(text).map_err(|error| error.to_string())?;
+    match (request.id, request.puzzle) {
+        (Some(id), None) if serialize::is_valid_id(&id) => Ok(serialize::load(&id)),
+        (Some(_), None) => Err("invalid id".to_string()),
+        (None, Some(puzzle)) => puzzle.to_game(),
+        _ => Err("send exactly one of id and puzzle".to_string()),
+    }
+}
+
+fn solve(
+    spec: GameSpec,
+    state: GameState,
+    received_at: Instant,
+    jobs: &SyncSender<Job>,
+    config: &ServiceConfig,
+) -> (u16, Value) {
+    let deadline = received_at + config.timeout;
+    let (reply, answer) = mpsc::channel();
+    let job = Job {
+        spec,
+        state,
+        queued_at: Instant::now(),
+        deadline,
+        reply,
+    };
+    match jobs.try_send(job) {
+        Ok(()) => {}
+        Err(TrySendError::Full(_)) => return (503, json!({ "error": "solver queue is full" })),
+        Err(TrySendError::Disconnected(_)) => return (503, json!({ "error": "shutting down" })),
+    }
+
+    // the worker checks the deadline itself; the extra second covers its check interval.
+    let wait = deadline.saturating_duration_since(Instant::now()) + Duration::from_secs(1);
+    let (result, queued) = match answer.recv_timeout(wait) {
+        Ok(answer) => answer,
+        Err(RecvTimeoutError::Timeout) => return (504, json!({ "error": "timed out" })),
+        Err(RecvTimeoutError::Disconnected) => return (500, json!({ "error": "solver stopped" })),
+    };
+    let stats = |stats: &SolveStats| {
+        json!({
+            "explored_states": stats.explored_states,
+            "queued_ms": queued.as_millis() as u64,
+            "solve_ms": stats.elapsed.as_millis() as u64,
+        })
+    };
+    match result {
+        Ok(solution) => (
+            200,
+            json!({
+                "moves": solution.moves,
+                "move_count": solution.moves.len(),
+                "stats": stats(&solution.stats),
+            }),
+        ),
+        Err(error) => {
+            let status = match error {
+                SolveError::TimedOut(_) => 504,
+                _ => 422,
+            };
+            let mut body = json!({ "error": error.to_string() });
+            if let SolveError::NoSolution(solve_stats)
+            | SolveError::TimedOut(solve_stats)
+            | SolveError::StateLimit(solve_stats) = &error
+            {
+                body["stats"] = stats(solve_stats);
+            }
+            (status, body)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{near_goal_id, ID};
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    fn post(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_solve_end_to_end() {
+        let service = Service::start("127.0.0.1:0", ServiceConfig::default()).unwrap();
+        let addr = service.addr();
+        let id = near_goal_id(&serialize::load(ID).0.goals[0]);
+
+        let (status, body) = post(addr, "POST", "/solve", &json!({ "id": id }).to_string());
+        assert_eq!(status, 200);
+        assert_eq!(body["move_count"], 1);
+        assert_eq!(body["moves"].as_array().unwrap().len(), 1);
+        assert!(body["stats"]["explored_states"].as_u64().unwrap() > 1);
+
+        // the same puzzle as json, answered by the same worker and solver.
+        let (spec, state) = serialize::load(&id);
+        let puzzle = Puzzle::from_game(&spec, &state);
+        let (status, json_body) = post(
+            addr,
+            "POST",
+            "/solve",
+            &json!({ "puzzle": puzzle }).to_string(),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(json_body["moves"], body["moves"]);
+
+        assert_eq!(post(addr, "POST", "/solve", "{\"id\": \"abc\"}").0, 400);
+        // an open top wall at (0, 0) would let a robot walk off the board
+        let borderless = format!("vK{}", &ID[2..]);
+        for _ in 0..ServiceConfig::default().handlers + 1 {
+            let (status, body) = post(
+                addr,
+                "POST",
+                "/solve",
+                &json!({ "id": borderless }).to_string(),
+            );
+            assert_eq!((status, body["error"].as_str()), (400, Some("invalid id")));
+        }
+        assert_eq!(post(addr, "POST", "/solve", "not json").0, 400);
+        assert_eq!(post(addr, "GET", "/solve", "").0, 405);
+        assert_eq!(post(addr, "POST", "/other", "").0, 404);
+        service.shutdown();
+    }
+
+    #[test]
+    fn test_timeout() {
+        let config = ServiceConfig {
+            timeout: Duration::ZERO,
+            ..ServiceConfig::default()
+        };
+        let service = Service::start("127.0.0.1:0", config).unwrap();
+        let (status, body) = post(
+            service.addr(),
+            "POST",
+            "/solve",
+            &json!({ "id": ID }).to_string(),
+        );
+        assert_eq!(status, 504);
+        assert!(body["error"].as_str().unwrap().contains("timed out"));
+        service.shutdown();
+    }
+}
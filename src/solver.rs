@@ -1,28 +1,56 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use web_time::{Duration, Instant};
 
-use bitvec::{bitvec, order::Msb0};
+use bitvec::{order::Msb0, vec::BitVec};
 
-use crate::model::{GameMove, GameSpec, GameState, GAME_MOVES};
+use crate::analysis::{self, Unsolvable};
+use crate::model::{GameMove, GameSpec, GameState, Goal, TargetType, BOARD_SIZE, GAME_MOVES};
 
-pub fn solve_bfs(spec: &GameSpec, initial_state: &GameState) -> Vec<GameMove> {
-    let mut back_edge = Vec::with_capacity(100_000_000);
-    let mut vis = bitvec![u64, Msb0; 0; 1 << 32];
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolutionCounting {
+    // every move sequence counts
+    Strict,
+    // sequences with no adjacent pair of moves by different robots that is out of
+    // move order and gets to the same state when swapped. each class of sequences
+    // linked by such swaps has at least one, its smallest, so this is never below
+    // the number of classes; it can be above, since whether two moves can be
+    // swapped depends on the state they start from. a count of one still means
+    // every optimal solution is the same up to these swaps.
+    ModuloIndependentMoves,
+}
+
+// the moves of an optimal solution; an empty list if the goal is already reached.
+pub fn solve_bfs(spec: &GameSpec, initial_state: &GameState) -> Result<Vec<GameMove>, SolveError> {
+    analysis::check_solvable(spec, initial_state).map_err(SolveError::Unsolvable)?;
+    if spec.is_winning_state(initial_state) {
+        return Ok(Vec::new());
+    }
+    let start = Instant::now();
+    // 1.6GB up front, more than a 32 bit target can address
+    let capacity = if cfg!(target_pointer_width = "64") {
+        100_000_000
+    } else {
+        0
+    };
+    let mut back_edge = Vec::with_capacity(capacity);
+    let mut vis = VisitedSet::full();
 
     let mut q = VecDeque::new();
     q.push_back(initial_state.clone());
-    vis.set(initial_state.to_u32() as usize, true);
+    vis.insert(initial_state.to_u32());
 
     let mut final_state: Option<GameState> = None;
     'mainloop: while !q.is_empty() {
         let current_state = q.pop_front().unwrap();
 
         for next_state in spec.next_states(&current_state).into_iter() {
-            let next_state_id = next_state.to_u32() as usize;
-            if *vis.get(next_state_id).unwrap() {
+            let next_state_id = next_state.to_u32();
+            if vis.contains(next_state_id) {
                 continue;
             }
             back_edge.push((next_state.clone(), current_state.clone()));
-            vis.set(next_state_id, true);
+            vis.insert(next_state_id);
             if spec.is_winning_state(&next_state) {
                 final_state = Some(next_state);
                 break 'mainloop;
@@ -31,10 +59,12 @@ pub fn solve_bfs(spec: &GameSpec, initial_state: &GameState) -> Vec<GameMove> {
         }
     }
 
-    if final_state.is_none() {
-        return Vec::new();
-    }
-    let mut state = final_state.unwrap();
+    let Some(mut state) = final_state else {
+        return Err(SolveError::NoSolution(SolveStats {
+            explored_states: back_edge.len() + 1,
+            elapsed: start.elapsed(),
+        }));
+    };
     let mut moves: Vec<GameMove> = vec![];
 
     while state != *initial_state {
@@ -51,5 +81,757 @@ pub fn solve_bfs(spec: &GameSpec, initial_state: &GameState) -> Vec<GameMove> {
         }
     }
     moves.reverse();
-    moves
+    Ok(moves)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolveStats {
+    pub explored_states: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub moves: Vec<GameMove>,
+    pub stats: SolveStats,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Unsolvable(Unsolvable),
+    // the search ran out of states without reaching the goal
+    NoSolution(SolveStats),
+    TimedOut(SolveStats),
+    // the search stopped at the solver's state limit
+    StateLimit(SolveStats),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Unsolvable(unsolvable) => write!(f, "{}", unsolvable),
+            SolveError::NoSolution(stats) => write!(
+                f,
+                "no solution after exploring {} states",
+                stats.explored_states
+            ),
+            SolveError::TimedOut(stats) => write!(
+                f,
+                "timed out after exploring {} states in {:.1}s",
+                stats.explored_states,
+                stats.elapsed.as_secs_f64()
+            ),
+            SolveError::StateLimit(stats) => write!(
+                f,
+                "gave up at the state limit after exploring {} states",
+                stats.explored_states
+            ),
+        }
+    }
+}
+
+// how many states to expand between deadline checks
+const DEADLINE_CHECK_INTERVAL: usize = 1 << 14;
+
+// memory of a `Solver`: the visited bitvec, plus this much per explored state.
+pub const VISITED_SET_BYTES: usize = 1 << 29;
+pub const STATE_BYTES: usize = std::mem::size_of::<(GameState, u32, u8)>();
+// memory of a compact `Solver` per explored state: the state and its hash set
+// entry, with room for the set to double.
+pub const COMPACT_STATE_BYTES: usize = STATE_BYTES + 12;
+
+// the states a search has seen, by `GameState::to_u32`.
+pub(crate) enum VisitedSet {
+    // one bit per possible state
+    #[cfg_attr(not(target_pointer_width = "64"), allow(dead_code))]
+    Bits(BitVec<usize, Msb0>),
+    // only the states seen; slower, but it grows with the search
+    Hashed(HashSet<u32>),
+}
+
+impl VisitedSet {
+    // a bit for every state: 512MB. 32 bit targets such as wasm can't address
+    // that much, so they get a hash set that grows with the search instead.
+    pub(crate) fn full() -> VisitedSet {
+        #[cfg(target_pointer_width = "64")]
+        return VisitedSet::Bits(bitvec::bitvec![usize, Msb0; 0; 1 << 32]);
+        #[cfg(not(target_pointer_width = "64"))]
+        return VisitedSet::Hashed(HashSet::new());
+    }
+
+    pub(crate) fn insert(&mut self, state_id: u32) {
+        match self {
+            VisitedSet::Bits(bits) => bits.set(state_id as usize, true),
+            VisitedSet::Hashed(set) => {
+                set.insert(state_id);
+            }
+        }
+    }
+
+    pub(crate) fn contains(&self, state_id: u32) -> bool {
+        match self {
+            VisitedSet::Bits(bits) => bits[state_id as usize],
+            VisitedSet::Hashed(set) => set.contains(&state_id),
+        }
+    }
+
+    // `visited` lists every state that was inserted.
+    fn clear(&mut self, visited: &[(GameState, u32, u8)]) {
+        match self {
+            VisitedSet::Bits(bits) => {
+                for (state, _, _) in visited {
+                    bits.set(state.to_u32() as usize, false);
+                }
+            }
+            VisitedSet::Hashed(set) => set.clear(),
+        }
+    }
+}
+
+enum SearchEnd {
+    // index of the first winning state
+    Found(usize),
+    Exhausted,
+    TimedOut,
+    StateLimit,
+}
+
+// a forward search that keeps its buffers between solves. the visited bitvec
+// is 512MB, so it is allocated once and only the bits that were set get cleared:
+// `visited` doubles as the list of touched bits. `Solver::compact` trades it for
+// a hash set, for callers such as wasm that can't spare the 512MB.
+pub struct Solver {
+    vis: VisitedSet,
+    // states in visiting order, with the index of the previous state and the move from it.
+    visited: Vec<(GameState, u32, u8)>,
+    // most states one search may keep; `visited` never grows past it.
+    state_limit: Option<usize>,
+}
+
+impl Default for Solver {
+    fn default() -> Solver {
+        Solver::new()
+    }
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver {
+            vis: VisitedSet::full(),
+            visited: Vec::new(),
+            state_limit: None,
+        }
+    }
+
+    // a solver that stays within `memory_budget` bytes, giving up with
+    // `StateLimit` once it has explored as many states as fit.
+    pub fn compact(memory_budget: usize) -> Solver {
+        Solver {
+            vis: VisitedSet::Hashed(HashSet::new()),
+            visited: Vec::new(),
+            state_limit: Some(memory_budget / COMPACT_STATE_BYTES),
+        }
+    }
+
+    pub fn set_state_limit(&mut self, state_limit: Option<usize>) {
+        self.state_limit = state_limit;
+        if let Some(state_limit) = state_limit {
+            self.visited.shrink_to(state_limit);
+        }
+    }
+
+    pub fn solve(
+        &mut self,
+        spec: &GameSpec,
+        initial_state: &GameState,
+        deadline: Option<Instant>,
+    ) -> Result<Solution, SolveError> {
+        analysis::check_solvable(spec, initial_state).map_err(SolveError::Unsolvable)?;
+        let start = Instant::now();
+        let found = self.search(spec, initial_state, deadline);
+        let stats = SolveStats {
+            explored_states: self.visited.len(),
+            elapsed: start.elapsed(),
+        };
+
+        let result = match found {
+            SearchEnd::Found(mut index) => {
+                let mut moves = Vec::new();
+                while index != 0 {
+                    let (_, prev, move_index) = &self.visited[index];
+                    moves.push(GAME_MOVES[*move_index as usize].clone());
+                    index = *prev as usize;
+                }
+                moves.reverse();
+                Ok(Solution { moves, stats })
+            }
+            SearchEnd::Exhausted => Err(SolveError::NoSolution(stats)),
+            SearchEnd::TimedOut => Err(SolveError::TimedOut(stats)),
+            SearchEnd::StateLimit => Err(SolveError::StateLimit(stats)),
+        };
+
+        self.vis.clear(&self.visited);
+        self.visited.clear();
+        result
+    }
+
+    fn search(
+        &mut self,
+        spec: &GameSpec,
+        initial_state: &GameState,
+        deadline: Option<Instant>,
+    ) -> SearchEnd {
+        let state_limit = self.state_limit.unwrap_or(usize::MAX).max(1);
+        self.visited.push((initial_state.clone(), 0, 0));
+        self.vis.insert(initial_state.to_u32());
+        if spec.is_winning_state(initial_state) {
+            return SearchEnd::Found(0);
+        }
+
+        let mut head = 0;
+        while head < self.visited.len() {
+            if head % DEADLINE_CHECK_INTERVAL == 0
+                && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                return SearchEnd::TimedOut;
+            }
+            let current_state = self.visited[head].0.clone();
+            for (i, next_state) in spec.next_states(&current_state).into_iter().enumerate() {
+                let next_state_id = next_state.to_u32();
+                if self.vis.contains(next_state_id) {
+                    continue;
+                }
+                if self.visited.len() == state_limit {
+                    return SearchEnd::StateLimit;
+                }
+                if self.visited.len() == self.visited.capacity() {
+                    // double as usual, but never past the limit
+                    let room = state_limit - self.visited.len();
+                    self.visited.reserve_exact(self.visited.len().min(room));
+                }
+                self.vis.insert(next_state_id);
+                let winning = spec.is_winning_state(&next_state);
+                self.visited.push((next_state, head as u32, i as u8));
+                if winning {
+                    return SearchEnd::Found(self.visited.len() - 1);
+                }
+            }
+            head += 1;
+        }
+        SearchEnd::Exhausted
+    }
+}
+
+// one forward search for many targets at once. each target is solved on its own,
+// as if it were the only goal. targets no robot can stop on are left out of the
+// search, and targets it doesn't reach get the reason it ended.
+pub fn solve_all_targets(
+    spec: &GameSpec,
+    initial_state: &GameState,
+    targets: &[Goal],
+    state_limit: Option<usize>,
+    deadline: Option<Instant>,
+) -> Vec<Result<Vec<GameMove>, SolveError>> {
+    let start = Instant::now();
+    let state_limit = state_limit.unwrap_or(usize::MAX).max(1);
+    let unsolvable: Vec<Option<Unsolvable>> = targets
+        .iter()
+        .map(|target| {
+            let target_spec = GameSpec::new(spec.walls, vec![target.clone()]);
+            analysis::check_solvable(&target_spec, initial_state).err()
+        })
+        .collect();
+    let mut targets_at = vec![vec![Vec::new(); BOARD_SIZE]; BOARD_SIZE];
+    for (i, target) in targets.iter().enumerate() {
+        if unsolvable[i].is_none() {
+            targets_at[target.position.r as usize][target.position.c as usize].push(i);
+        }
+    }
+
+    // states in visiting order, with the index of the previous state and the move from it.
+    let mut visited: Vec<(GameState, u32, u8)> = vec![(initial_state.clone(), 0, 0)];
+    let mut found: Vec<Option<usize>> = vec![None; targets.len()];
+    let mut remaining = unsolvable
+        .iter()
+        .filter(|unsolvable| unsolvable.is_none())
+        .count();
+    let mut vis = VisitedSet::full();
+    vis.insert(initial_state.to_u32());
+
+    let mut head = 0;
+    let end = 'search: loop {
+        if head == visited.len() {
+            break SearchEnd::Exhausted;
+        }
+        let current_state = visited[head].0.clone();
+        for (robot_index, robot) in current_state.robots.iter().enumerate() {
+            for &i in &targets_at[robot.r as usize][robot.c as usize] {
+                let matches = match targets[i].target_type {
+                    TargetType::Any => true,
+                    TargetType::Particular(target_robot) => target_robot == robot_index,
+                };
+                if matches && found[i].is_none() {
+                    found[i] = Some(head);
+                    remaining -= 1;
+                }
+            }
+        }
+        if remaining == 0 {
+            break SearchEnd::Found(head);
+        }
+        if head % DEADLINE_CHECK_INTERVAL == 0
+            && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            break SearchEnd::TimedOut;
+        }
+
+        for (i, next_state) in spec.next_states(&current_state).into_iter().enumerate() {
+            let next_state_id = next_state.to_u32();
+            if vis.contains(next_state_id) {
+                continue;
+            }
+            if visited.len() == state_limit {
+                break 'search SearchEnd::StateLimit;
+            }
+            vis.insert(next_state_id);
+            visited.push((next_state, head as u32, i as u8));
+        }
+        head += 1;
+    };
+    let stats = SolveStats {
+        explored_states: visited.len(),
+        elapsed: start.elapsed(),
+    };
+
+    found
+        .into_iter()
+        .zip(unsolvable)
+        .map(|(found, unsolvable)| {
+            if let Some(unsolvable) = unsolvable {
+                return Err(SolveError::Unsolvable(unsolvable));
+            }
+            let Some(mut index) = found else {
+                return Err(match end {
+                    SearchEnd::TimedOut => SolveError::TimedOut(stats),
+                    SearchEnd::StateLimit => SolveError::StateLimit(stats),
+                    _ => SolveError::NoSolution(stats),
+                });
+            };
+            let mut moves = Vec::new();
+            while index != 0 {
+                let (_, prev, move_index) = &visited[index];
+                moves.push(GAME_MOVES[*move_index as usize].clone());
+                index = *prev as usize;
+            }
+            moves.reverse();
+            Ok(moves)
+        })
+        .collect()
+}
+
+// the shortest paths from an initial state, kept as the sorted state ids of each
+// distance up to the first layer that has a winning state.
+pub struct OptimalPaths<'a> {
+    spec: &'a GameSpec,
+    initial_state: GameState,
+    layers: Vec<Vec<u32>>,
+}
+
+impl<'a> OptimalPaths<'a> {
+    // none if no winning state is reachable.
+    pub fn new(spec: &'a GameSpec, initial_state: &GameState) -> Option<Self> {
+        let mut vis = VisitedSet::full();
+        vis.insert(initial_state.to_u32());
+        let mut layers = vec![vec![initial_state.to_u32()]];
+        let mut layer = vec![initial_state.clone()];
+        while !layer.iter().any(|state| spec.is_winning_state(state)) {
+            let mut next_layer = Vec::new();
+            for current_state in &layer {
+                for next_state in spec.next_states(current_state).into_iter() {
+                    let next_state_id = next_state.to_u32();
+                    if vis.contains(next_state_id) {
+                        continue;
+                    }
+                    vis.insert(next_state_id);
+                    next_layer.push(next_state);
+                }
+            }
+            if next_layer.is_empty() {
+                return None;
+            }
+            let mut ids: Vec<u32> = next_layer.iter().map(GameState::to_u32).collect();
+            ids.sort_unstable();
+            layers.push(ids);
+            layer = next_layer;
+        }
+        Some(Self {
+            spec,
+            initial_state: initial_state.clone(),
+            layers,
+        })
+    }
+
+    fn at_distance(&self, state: &GameState, distance: usize) -> bool {
+        self.layers[distance].binary_search(&state.to_u32()).is_ok()
+    }
+
+    // the number of optimal solutions, saturating at usize::MAX.
+    pub fn count(&self, counting: SolutionCounting) -> usize {
+        let mut memo = HashMap::new();
+        self.count_from(&self.initial_state, 0, None, counting, &mut memo)
+    }
+
+    // solutions from `state` at `distance`, which `last` reached from the given
+    // state when counting modulo independent moves. a move that could have been
+    // swapped with the one before it and comes first in move order is skipped.
+    fn count_from(
+        &self,
+        state: &GameState,
+        distance: usize,
+        last: Option<(&GameState, usize)>,
+        counting: SolutionCounting,
+        memo: &mut HashMap<(u32, u8), usize>,
+    ) -> usize {
+        if distance + 1 == self.layers.len() {
+            return self.spec.is_winning_state(state) as usize;
+        }
+        let key = match last {
+            Some((prev_state, move_index)) => (prev_state.to_u32(), move_index as u8),
+            None => (state.to_u32(), u8::MAX),
+        };
+        if let Some(&count) = memo.get(&key) {
+            return count;
+        }
+
+        let mut count = 0usize;
+        for (i, next_state) in self.spec.next_states(state).into_iter().enumerate() {
+            if !self.at_distance(&next_state, distance + 1) {
+                continue;
+            }
+            if let Some((prev_state, move_index)) = last {
+                let swapped_first = self.spec.apply_move(prev_state, &GAME_MOVES[i]);
+                if i < move_index
+                    && GAME_MOVES[i].robot_index != GAME_MOVES[move_index].robot_index
+                    && self
+                        .spec
+                        .apply_move(&swapped_first, &GAME_MOVES[move_index])
+                        == next_state
+                {
+                    continue;
+                }
+            }
+            let next_last = match counting {
+                SolutionCounting::Strict => None,
+                SolutionCounting::ModuloIndependentMoves => Some((state, i)),
+            };
+            count = count.saturating_add(self.count_from(
+                &next_state,
+                distance + 1,
+                next_last,
+                counting,
+                memo,
+            ));
+        }
+        memo.insert(key, count);
+        count
+    }
+
+    // the optimal solutions in move order, up to `limit` of them.
+    pub fn solutions(&self, limit: usize) -> Vec<Vec<GameMove>> {
+        let mut memo = HashMap::new();
+        let mut solutions = Vec::new();
+        let mut moves = Vec::new();
+        self.collect_solutions(
+            &self.initial_state,
+            limit,
+            &mut memo,
+            &mut moves,
+            &mut solutions,
+        );
+        solutions
+    }
+
+    fn collect_solutions(
+        &self,
+        state: &GameState,
+        limit: usize,
+        memo: &mut HashMap<(u32, u8), usize>,
+        moves: &mut Vec<GameMove>,
+        solutions: &mut Vec<Vec<GameMove>>,
+    ) {
+        if moves.len() + 1 == self.layers.len() {
+            solutions.push(moves.clone());
+            return;
+        }
+        for (i, next_state) in self.spec.next_states(state).into_iter().enumerate() {
+            if solutions.len() >= limit {
+                return;
+            }
+            let distance = moves.len() + 1;
+            if !self.at_distance(&next_state, distance)
+                || self.count_from(&next_state, distance, None, SolutionCounting::Strict, memo) == 0
+            {
+                continue;
+            }
+            moves.push(GAME_MOVES[i].clone());
+            self.collect_solutions(&next_state, limit, memo, moves, solutions);
+            moves.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{near_goal_state, small_spec, ID};
+    use crate::model::Point;
+    use crate::serialize;
+
+    #[test]
+    fn test_optimal_solutions() {
+        let (spec, state) = serialize::load(ID);
+        assert!(matches!(
+            spec.goals[0].target_type,
+            TargetType::Particular(_)
+        ));
+        let state = near_goal_state(&spec, &state, &spec.goals[0], 2);
+
+        let best = solve_bfs(&spec, &state).unwrap();
+        let paths = OptimalPaths::new(&spec, &state).unwrap();
+        let solutions = paths.solutions(usize::MAX);
+        assert!(solutions.contains(&best));
+        for moves in &solutions {
+            assert_eq!(moves.len(), best.len());
+            let last = moves
+                .iter()
+                .fold(state.clone(), |s, m| spec.apply_move(&s, m));
+            assert!(spec.is_winning_state(&last));
+        }
+        assert_eq!(paths.solutions(1).len(), 1);
+
+        let strict = paths.count(SolutionCounting::Strict);
+        let modulo = paths.count(SolutionCounting::ModuloIndependentMoves);
+        assert_eq!(strict, solutions.len());
+        assert!(1 <= modulo && modulo <= strict);
+    }
+
+    #[test]
+    fn test_solve_bfs_errors() {
+        let (spec, state) = serialize::load(ID);
+        let solved = near_goal_state(&spec, &state, &spec.goals[0], 0);
+        assert_eq!(solve_bfs(&spec, &solved), Ok(Vec::new()));
+
+        // the walled off center of the board
+        let center = Goal {
+            position: Point::new(7, 7),
+            target_type: TargetType::Any,
+        };
+        let spec = GameSpec::new(spec.walls, vec![center]);
+        assert!(matches!(
+            solve_bfs(&spec, &state),
+            Err(SolveError::Unsolvable(_))
+        ));
+    }
+
+    #[test]
+    fn test_solve_all_targets() {
+        let (spec, state) = serialize::load(ID);
+        let near_state = near_goal_state(&spec, &state, &spec.goals[0], 2);
+        let goal = spec.goals[0].clone();
+        let robot_index = goal.target_type.robot_index(0);
+        let satisfied = Goal {
+            position: near_state.robots[robot_index],
+            target_type: TargetType::Any,
+        };
+
+        let solutions = solve_all_targets(&spec, &near_state, &[goal, satisfied], None, None);
+        assert_eq!(
+            solutions[0].as_ref().unwrap().len(),
+            solve_bfs(&spec, &near_state).unwrap().len()
+        );
+        let last = solutions[0]
+            .as_ref()
+            .unwrap()
+            .iter()
+            .fold(near_state.clone(), |s, m| spec.apply_move(&s, m));
+        assert!(spec.is_winning_state(&last));
+        assert_eq!(solutions[1], Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_solve_all_targets_limits() {
+        let (spec, state) = serialize::load(ID);
+        let goal = spec.goals[0].clone();
+        // the walled off center of the board
+        let center = Goal {
+            position: Point::new(7, 7),
+            target_type: TargetType::Any,
+        };
+        let targets = [goal, center];
+
+        let solutions = solve_all_targets(&spec, &state, &targets, None, Some(Instant::now()));
+        assert!(matches!(solutions[0], Err(SolveError::TimedOut(_))));
+        assert!(matches!(solutions[1], Err(SolveError::Unsolvable(_))));
+
+        let solutions = solve_all_targets(&spec, &state, &targets, Some(100), None);
+        match &solutions[0] {
+            Err(SolveError::StateLimit(stats)) => assert_eq!(stats.explored_states, 100),
+            result => panic!("expected the state limit, got {:?}", result),
+        }
+        assert!(matches!(solutions[1], Err(SolveError::Unsolvable(_))));
+    }
+
+    #[test]
+    fn test_solver_reuses_buffers() {
+        let (spec, state) = serialize::load(ID);
+        let near_state = near_goal_state(&spec, &state, &spec.goals[0], 2);
+        let mut solver = Solver::new();
+
+        let first = solver.solve(&spec, &near_state, None).unwrap();
+        assert_eq!(
+            first.moves.len(),
+            solve_bfs(&spec, &near_state).unwrap().len()
+        );
+        assert!(first.stats.explored_states > 1);
+        // a second solve must not see states visited by the first one.
+        let second = solver.solve(&spec, &near_state, None).unwrap();
+        assert_eq!(second.moves, first.moves);
+        assert_eq!(second.stats.explored_states, first.stats.explored_states);
+
+        assert!(matches!(
+            solver.solve(&spec, &state, Some(Instant::now())),
+            Err(SolveError::TimedOut(_))
+        ));
+    }
+
+    #[test]
+    fn test_compact_solver() {
+        let (spec, state) = serialize::load(ID);
+        let near_state = near_goal_state(&spec, &state, &spec.goals[0], 2);
+        let expected = Solver::new().solve(&spec, &near_state, None).unwrap();
+
+        let mut solver = Solver::compact(64 << 20);
+        for _ in 0..2 {
+            let solution = solver.solve(&spec, &near_state, None).unwrap();
+            assert_eq!(solution.moves, expected.moves);
+            assert_eq!(
+                solution.stats.explored_states,
+                expected.stats.explored_states
+            );
+        }
+        let mut tiny = Solver::compact(10 * COMPACT_STATE_BYTES);
+        match tiny.solve(&spec, &state, None) {
+            Err(SolveError::StateLimit(stats)) => assert_eq!(stats.explored_states, 10),
+            result => panic!("expected the state limit, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_count_modulo_independent_moves() {
+        let (spec, state) = serialize::load(ID);
+        let (a, b, ab) = GAME_MOVES
+            .iter()
+            .flat_map(|a| GAME_MOVES.iter().map(move |b| (a, b)))
+            .find_map(|(a, b)| {
+                let ab = spec.apply_move(&spec.apply_move(&state, a), b);
+                let ba = spec.apply_move(&spec.apply_move(&state, b), a);
+                let moved = |m: &GameMove| {
+                    let i = m.robot_index as usize;
+                    ab.robots[i] != state.robots[i]
+                };
+                (a.robot_index != b.robot_index && ab == ba && moved(a) && moved(b))
+                    .then_some((a, b, ab))
+            })
+            .unwrap();
+
+        // both robots have to make their one move, in either order.
+        let goals = [a, b]
+            .iter()
+            .map(|m| Goal {
+                position: ab.robots[m.robot_index as usize],
+                target_type: TargetType::Particular(m.robot_index as usize),
+            })
+            .collect();
+        let spec = GameSpec::new(spec.walls, goals);
+        let paths = OptimalPaths::new(&spec, &state).unwrap();
+        assert_eq!(paths.count(SolutionCounting::Strict), 2);
+        assert_eq!(paths.count(SolutionCounting::ModuloIndependentMoves), 1);
+    }
+
+    // the sequences ModuloIndependentMoves promises to count, and the number of
+    // classes of sequences linked by swapping independent moves.
+    fn count_by_hand(
+        spec: &GameSpec,
+        state: &GameState,
+        solutions: &[Vec<GameMove>],
+    ) -> (usize, usize) {
+        let index = |m: &GameMove| m.robot_index as usize * 4 + m.direction as usize;
+        let mut class: Vec<usize> = (0..solutions.len()).collect();
+        let mut sorted = 0;
+        for (i, solution) in solutions.iter().enumerate() {
+            let mut current = state.clone();
+            let mut is_sorted = true;
+            for k in 0..solution.len() {
+                let (a, b) = (&solution[k], solution.get(k + 1));
+                if let Some(b) = b.filter(|b| b.robot_index != a.robot_index) {
+                    let ab = spec.apply_move(&spec.apply_move(&current, a), b);
+                    let ba = spec.apply_move(&spec.apply_move(&current, b), a);
+                    if ab == ba {
+                        is_sorted &= index(a) < index(b);
+                        let mut swapped = solution.clone();
+                        swapped.swap(k, k + 1);
+                        let j = solutions
+                            .iter()
+                            .position(|other| *other == swapped)
+                            .unwrap();
+                        let (from, to) = (class[i].max(class[j]), class[i].min(class[j]));
+                        class
+                            .iter_mut()
+                            .filter(|c| **c == from)
+                            .for_each(|c| *c = to);
+                    }
+                }
+                current = spec.apply_move(&current, a);
+            }
+            sorted += is_sorted as usize;
+        }
+        let classes = class.iter().enumerate().filter(|&(i, c)| i == *c).count();
+        (sorted, classes)
+    }
+
+    #[test]
+    fn test_count_modulo_by_brute_force() {
+        let cells: Vec<Point> = (0..3)
+            .flat_map(|r| (0..3).map(move |c| Point::new(r, c)))
+            .collect();
+        let goal = Goal {
+            position: Point::new(2, 2),
+            target_type: TargetType::Particular(1),
+        };
+        let spec = small_spec(goal);
+        let mut fewer_classes = false;
+        // cells of the robots, read row by row
+        let placements = [
+            [2, 0, 4, 7],
+            [1, 0, 8, 6],
+            [8, 0, 6, 2],
+            [5, 1, 8, 7],
+            [6, 3, 8, 5],
+            [7, 0, 5, 4],
+        ];
+        for placement in placements {
+            let state = GameState {
+                robots: placement.map(|i| cells[i]),
+            };
+            let paths = OptimalPaths::new(&spec, &state).unwrap();
+            let solutions = paths.solutions(usize::MAX);
+            let (sorted, classes) = count_by_hand(&spec, &state, &solutions);
+            let modulo = paths.count(SolutionCounting::ModuloIndependentMoves);
+            assert_eq!(paths.count(SolutionCounting::Strict), solutions.len());
+            assert_eq!(modulo, sorted, "{:?}", state);
+            assert!(1 <= classes && classes <= modulo);
+            fewer_classes |= classes < modulo;
+        }
+        assert!(fewer_classes);
+    }
 }
@@ -0,0 +1,70 @@
+// a wasm-bindgen api for solving in the browser. it runs the compact solver,
+// whose memory grows with the search up to a budget instead of starting with
+// the 512MB visited bitvec.
+
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use ricochet_robots::{serialize, solver::Solver};
+
+// bytes, when the caller doesn't give a budget
+pub const DEFAULT_MEMORY_BUDGET: usize = 64 << 20;
+
+fn board_id(id: &str) -> &str {
+    id.trim().split("id=").last().unwrap()
+}
+
+fn solve_id(id: &str, memory_budget: usize) -> Result<String, String> {
+    let id = board_id(id);
+    if !serialize::is_valid_id(id) {
+        return Err(format!("invalid board id: {}", id));
+    }
+    let (spec, state) = serialize::load(id);
+    let solution = Solver::compact(memory_budget)
+        .solve(&spec, &state, None)
+        .map_err(|error| error.to_string())?;
+    Ok(json!({
+        "moves": solution.moves,
+        "move_count": solution.moves.len(),
+        "explored_states": solution.stats.explored_states,
+    })
+    .to_string())
+}
+
+#[wasm_bindgen(js_name = isValidId)]
+pub fn is_valid_id(id: &str) -> bool {
+    serialize::is_valid_id(board_id(id))
+}
+
+// solves a kaseken id or replay url. returns json:
+// {"moves": [{"robot_index": 0, "direction": "Up"}, ...], "move_count": 1, "explored_states": 7}
+// and throws for invalid ids, unsolvable puzzles and searches that need more
+// than `memory_budget` bytes.
+#[wasm_bindgen]
+pub fn solve(id: &str, memory_budget: Option<u32>) -> Result<String, JsError> {
+    let memory_budget = memory_budget.map_or(DEFAULT_MEMORY_BUDGET, |budget| budget as usize);
+    solve_id(id, memory_budget).map_err(|message| JsError::new(&message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a 7 move puzzle from input.txt
+    const ID: &str = "rKHKNKXIrKM--7--_n---m-----__--Vv------Yv-N-B-_-----Ln-L-m--L-RJk---_XRL--R_-X-g1--j-n--NZ07-XXZf----K--N-BL---Vv----7----X----Zk-----Lj--R__---RLX--n-m------_leXKXAXKVeWDn94ytfICCphpWL97KIP4sVqKkqtUi";
+
+    #[test]
+    fn test_solve_id() {
+        let result: serde_json::Value =
+            serde_json::from_str(&solve_id(ID, DEFAULT_MEMORY_BUDGET).unwrap()).unwrap();
+        assert_eq!(result["move_count"], 7);
+        assert_eq!(result["moves"].as_array().unwrap().len(), 7);
+
+        let error = solve_id(ID, 1 << 10).unwrap_err();
+        assert!(error.contains("state limit"), "{}", error);
+        assert!(solve_id("garbage", DEFAULT_MEMORY_BUDGET).is_err());
+        // an open top wall at (0, 0) would let a robot walk off the board
+        let borderless = format!("vK{}", &ID[2..]);
+        assert!(solve_id(&borderless, DEFAULT_MEMORY_BUDGET).is_err());
+    }
+}
@@ -0,0 +1,35 @@
+// run headless in node with
+// cargo test -p ricochet_robots_wasm --target wasm32-unknown-unknown
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen_test::*;
+
+use ricochet_robots::{serialize, solver};
+use ricochet_robots_wasm::{is_valid_id, solve};
+
+// a 7 move puzzle from input.txt
+const ID: &str = "rKHKNKXIrKM--7--_n---m-----__--Vv------Yv-N-B-_-----Ln-L-m--L-RJk---_XRL--R_-X-g1--j-n--NZ07-XXZf----K--N-BL---Vv----7----X----Zk-----Lj--R__---RLX--n-m------_leXKXAXKVeWDn94ytfICCphpWL97KIP4sVqKkqtUi";
+
+#[wasm_bindgen_test]
+fn test_solve() {
+    assert!(is_valid_id(ID));
+    let result: serde_json::Value = serde_json::from_str(&solve(ID, None).unwrap()).unwrap();
+    assert_eq!(result["move_count"], 7);
+    assert_eq!(result["moves"].as_array().unwrap().len(), 7);
+
+    assert!(!is_valid_id("garbage"));
+    assert!(solve("garbage", None).is_err());
+    let borderless = format!("vK{}", &ID[2..]);
+    assert!(!is_valid_id(&borderless));
+    assert!(solve(&borderless, None).is_err());
+    assert!(solve(ID, Some(1 << 10)).is_err());
+}
+
+// the searches that use a bit per state on 64 bit targets fall back to a hash set
+#[wasm_bindgen_test]
+fn test_full_visited_set() {
+    let (spec, state) = serialize::load(ID);
+    assert_eq!(solver::solve_bfs(&spec, &state).unwrap().len(), 7);
+    let solution = solver::Solver::new().solve(&spec, &state, None).unwrap();
+    assert_eq!(solution.moves.len(), 7);
+}